@@ -1,7 +1,7 @@
 use dioxus::prelude::*;
 
 use dioxus_charts::charts::pie::LabelPosition;
-use dioxus_charts::{BarChart, LineChart, PieChart};
+use dioxus_charts::{BarChart, LineChart, LineStyle, PieChart, SeriesStyle};
 
 pub fn demo_element() -> Element {
     rsx! {
@@ -29,7 +29,7 @@ pub fn demo_element() -> Element {
                                 padding_left: 70,
                                 padding_right: 50,
                                 padding_bottom: 30,
-                                bar_width: "10%",
+                                bar_padding_outer: 0.3,
                                 horizontal_bars: true,
                                 label_interpolation: (|v| format!("{v}%")) as fn(f32) -> String,
                                 series: vec![
@@ -53,7 +53,6 @@ pub fn demo_element() -> Element {
                                 padding_left: 80,
                                 padding_right: 60,
                                 padding_bottom: 30,
-                                bar_distance: 25.0,
                                 horizontal_bars: true,
                                 label_size: 70,
                                 label_interpolation: (|v| format!("${v:.0}B")) as fn(f32) -> String,
@@ -79,8 +78,6 @@ pub fn demo_element() -> Element {
                                 padding_left: 50,
                                 padding_right: 30,
                                 padding_bottom: 40,
-                                bar_width: "3.5%",
-                                bar_distance: 20.0,
                                 show_series_labels: false,
                                 label_interpolation: (|v| format!("{v}%")) as fn(f32) -> String,
                                 label_size: 70,
@@ -143,6 +140,8 @@ pub fn demo_element() -> Element {
                                 ],
                                 labels: vec!["2020A".into(), "2021E".into(), "2022E".into(), "2023E".into(), "2024E".into()],
                                 series_labels: vec!["Disney".into(), "Comcast".into(), "Warner".into(), "Netflix".into()],
+                                // Only 2020 is actual spending, the line is dashed from it on
+                                series_styles: vec![SeriesStyle::default().with_line_style(LineStyle::Dashed).with_dash_from(0); 4],
                             }
                         }
                         h3 {
@@ -191,7 +190,6 @@ pub fn demo_element() -> Element {
                                 height: "100%",
                                 start_angle: -60.0,
                                 label_position: LabelPosition::Outside,
-                                label_offset: 35.0,
                                 padding: 20.0,
                                 series: vec![59.54, 17.2, 9.59, 7.6, 5.53, 0.55],
                                 labels: vec!["Asia".into(), "Africa".into(), "Europe".into(), "N. America".into(), "S. America".into(), "Oceania".into()],
@@ -212,7 +210,6 @@ pub fn demo_element() -> Element {
                                 height: "100%",
                                 start_angle: 50.0,
                                 label_position: LabelPosition::Outside,
-                                label_offset: 27.0,
                                 donut: true,
                                 padding: 20.0,
                                 series: vec![5.0, 4.0, 4.0, 2.0, 2.0, 2.0, 1.0, 1.0],
@@ -258,7 +255,6 @@ pub fn demo_element() -> Element {
                                 padding_left: 100,
                                 padding_right: 70,
                                 padding_bottom: 30,
-                                bar_width: "6%",
                                 horizontal_bars: true,
                                 viewbox_width: 600,
                                 viewbox_height: 500,
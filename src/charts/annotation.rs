@@ -0,0 +1,405 @@
+use dioxus::prelude::*;
+
+use crate::types::{Point, Rect, TextData};
+use crate::utils::text_width;
+
+/// A reference line, shaded range or text drawn over a [`LineChart`](crate::LineChart) or
+/// [`BarChart`](crate::BarChart), positioned with the chart world coordinates.
+///
+/// Values are on the value axis, like the series values, and positions are on the labels axis,
+/// where `0.0` is the first label, `1.0` the second one and so on, with fractions in between.
+/// Value lines run across the chart, i.e. horizontally unless the bars are horizontal.
+///
+/// ```rust,ignore
+/// let annotations = vec![
+///     Annotation::value_line(99.9).with_label("SLA").extend_domain(),
+///     Annotation::position_range(3.0, 4.5).with_label("Maintenance"),
+///     Annotation::text(6.0, 42.0, "Peak"),
+/// ];
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct Annotation {
+    kind: AnnotationKind,
+    label: Option<String>,
+    class: Option<String>,
+    extend_domain: bool,
+}
+
+#[derive(Clone, PartialEq)]
+enum AnnotationKind {
+    ValueLine(f32),
+    PositionLine(f32),
+    ValueRange(f32, f32),
+    PositionRange(f32, f32),
+    Text(f32, f32),
+}
+
+impl Annotation {
+    fn new(kind: AnnotationKind) -> Self {
+        Self {
+            kind,
+            label: None,
+            class: None,
+            extend_domain: false,
+        }
+    }
+
+    /// A reference line at `value`, i.e. a target or a threshold.
+    pub fn value_line(value: f32) -> Self {
+        Self::new(AnnotationKind::ValueLine(value))
+    }
+
+    /// A reference line at `position` on the labels axis, i.e. an event.
+    pub fn position_line(position: f32) -> Self {
+        Self::new(AnnotationKind::PositionLine(position))
+    }
+
+    /// A shaded range between the `from` and `to` values.
+    pub fn value_range(from: f32, to: f32) -> Self {
+        Self::new(AnnotationKind::ValueRange(from, to))
+    }
+
+    /// A shaded range between the `from` and `to` positions on the labels axis, i.e. a
+    /// maintenance window.
+    pub fn position_range(from: f32, to: f32) -> Self {
+        Self::new(AnnotationKind::PositionRange(from, to))
+    }
+
+    /// A `text` centered at `position` on the labels axis and `value` on the value axis.
+    pub fn text(position: f32, value: f32, text: impl Into<String>) -> Self {
+        Self::new(AnnotationKind::Text(position, value)).with_label(text)
+    }
+
+    /// The label shown along the line or inside the range.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// An extra HTML `class` for the annotation elements.
+    pub fn with_class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    /// Includes the annotation values in the generated value axis, so i.e. a target above the
+    /// series values is still visible. Positions don't extend the labels axis.
+    pub fn extend_domain(mut self) -> Self {
+        self.extend_domain = true;
+        self
+    }
+
+    // The values of the annotations that extend the value axis
+    pub(crate) fn domain_values(annotations: Option<&Vec<Annotation>>) -> Vec<f32> {
+        annotations
+            .into_iter()
+            .flatten()
+            .filter(|annotation| annotation.extend_domain)
+            .flat_map(|annotation| match annotation.kind {
+                AnnotationKind::ValueLine(value) | AnnotationKind::Text(_, value) => vec![value],
+                AnnotationKind::ValueRange(from, to) => vec![from, to],
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    // The annotation geometry on the `view` of the plot area, with the values mapped by
+    // `value_to_view` and the positions by `position_to_view`. The value axis is the horizontal
+    // one when `horizontal` is set.
+    pub(crate) fn to_view(
+        &self,
+        view: Rect,
+        value_to_view: impl Fn(f32) -> f32,
+        position_to_view: impl Fn(f32) -> f32,
+        horizontal: bool,
+    ) -> ViewAnnotation {
+        let point = |position: f32, value: f32| {
+            if horizontal {
+                Point::new(value, position)
+            } else {
+                Point::new(position, value)
+            }
+        };
+        let rect =
+            |a: Point, b: Point| Rect::new(a.x.min(b.x), a.y.min(b.y), a.x.max(b.x), a.y.max(b.y));
+
+        // The plot limits on the labels and the value axes
+        let (positions, values) = if horizontal {
+            ((view.min.y, view.max.y), (view.min.x, view.max.x))
+        } else {
+            ((view.min.x, view.max.x), (view.max.y, view.min.y))
+        };
+
+        let (shape, geometry) = match self.kind {
+            AnnotationKind::ValueLine(value) => {
+                let value = value_to_view(value);
+                let line = rect(point(positions.0, value), point(positions.1, value));
+                (AnnotationShape::Line, line)
+            }
+            AnnotationKind::PositionLine(position) => {
+                let position = position_to_view(position);
+                let line = rect(point(position, values.0), point(position, values.1));
+                (AnnotationShape::Line, line)
+            }
+            AnnotationKind::ValueRange(from, to) => {
+                let area = rect(
+                    point(positions.0, value_to_view(from)),
+                    point(positions.1, value_to_view(to)),
+                );
+                (AnnotationShape::Area, area)
+            }
+            AnnotationKind::PositionRange(from, to) => {
+                let area = rect(
+                    point(position_to_view(from), values.0),
+                    point(position_to_view(to), values.1),
+                );
+                (AnnotationShape::Area, area)
+            }
+            AnnotationKind::Text(position, value) => {
+                let center = point(position_to_view(position), value_to_view(value));
+                (
+                    AnnotationShape::Text,
+                    Rect::new(center.x, center.y, center.x, center.y),
+                )
+            }
+        };
+
+        // Line labels go above the end of horizontal lines and next to the top of vertical ones,
+        // range labels in the top left corner
+        let label_data = match shape {
+            AnnotationShape::Line if geometry.height() == 0.0 => TextData {
+                x: geometry.max.x,
+                y: geometry.min.y - 4.0,
+                anchor: "end",
+                baseline: "text-bottom",
+            },
+            AnnotationShape::Line | AnnotationShape::Area => TextData {
+                x: geometry.min.x + 4.0,
+                y: geometry.min.y + 4.0,
+                anchor: "start",
+                baseline: "hanging",
+            },
+            AnnotationShape::Text => TextData {
+                x: geometry.min.x,
+                y: geometry.min.y,
+                anchor: "middle",
+                baseline: "middle",
+            },
+        };
+
+        ViewAnnotation {
+            shape,
+            geometry,
+            label: self.label.clone().map(|label| (label_data, label)),
+            class: self.class.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum AnnotationShape {
+    Line,
+    Area,
+    Text,
+}
+
+// An annotation mapped to the view
+#[derive(Clone)]
+pub(crate) struct ViewAnnotation {
+    shape: AnnotationShape,
+    geometry: Rect,
+    label: Option<(TextData, String)>,
+    class: Option<String>,
+}
+
+// The annotation ranges are drawn beneath the series and the lines and labels above them, so
+// this renders either the `below` or the above layer
+pub(crate) fn annotations_element(
+    annotations: &[ViewAnnotation],
+    class: &str,
+    below: bool,
+) -> Element {
+    let element_class = |annotation: &ViewAnnotation, suffix: &str| match &annotation.class {
+        Some(extra) => format!("{class}-{suffix} {extra}"),
+        None => format!("{class}-{suffix}"),
+    };
+
+    rsx! {
+        g {
+            class: "{class}",
+            for annotation in annotations.iter() {
+                if below && annotation.shape == AnnotationShape::Area {
+                    rect {
+                        x: "{annotation.geometry.min.x}",
+                        y: "{annotation.geometry.min.y}",
+                        width: "{annotation.geometry.width()}",
+                        height: "{annotation.geometry.height()}",
+                        class: element_class(annotation, "range"),
+                        fill: "rgba(40, 40, 40, 0.1)",
+                    }
+                }
+                if !below && annotation.shape == AnnotationShape::Line {
+                    line {
+                        x1: "{annotation.geometry.min.x}",
+                        y1: "{annotation.geometry.min.y}",
+                        x2: "{annotation.geometry.max.x}",
+                        y2: "{annotation.geometry.max.y}",
+                        class: element_class(annotation, "line"),
+                        stroke: "rgb(40, 40, 40)",
+                        stroke_dasharray: "6 3",
+                    }
+                }
+                if !below {
+                    for (text, label) in annotation.label.iter() {
+                        text {
+                            dx: "{text.x}",
+                            dy: "{text.y}",
+                            text_anchor: "{text.anchor}",
+                            alignment_baseline: "{text.baseline}",
+                            class: element_class(annotation, "label"),
+                            "{label}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+const CALLOUT_HEIGHT: f32 = 20.0;
+const CALLOUT_PADDING: f32 = 6.0;
+const CALLOUT_OFFSET: f32 = 20.0;
+
+/// A text box pointing to a single series value with a leader line, i.e. to mark a deploy on a
+/// latency spike. The box is placed above and to the right of the value, or flipped to the other
+/// sides when it would go past the plot edges.
+#[derive(Clone, PartialEq)]
+pub struct Callout {
+    series: usize,
+    index: usize,
+    text: String,
+    class: Option<String>,
+}
+
+impl Callout {
+    /// Creates the callout with the `text` for the value at `index` of the series at `series`.
+    pub fn new(series: usize, index: usize, text: impl Into<String>) -> Self {
+        Self {
+            series,
+            index,
+            text: text.into(),
+            class: None,
+        }
+    }
+
+    /// An extra HTML `class` for the callout elements.
+    pub fn with_class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    pub(crate) fn series(&self) -> usize {
+        self.series
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
+    // The callout geometry for the value at `anchor`, kept inside the `view` of the plot area
+    pub(crate) fn to_view(&self, anchor: Point, view: Rect) -> ViewCallout {
+        let width = text_width(&self.text) + 2.0 * CALLOUT_PADDING;
+
+        let right = anchor.x + CALLOUT_OFFSET + width <= view.max.x;
+        let above = anchor.y - CALLOUT_OFFSET - CALLOUT_HEIGHT >= view.min.y;
+
+        let x = if right {
+            anchor.x + CALLOUT_OFFSET
+        } else {
+            anchor.x - CALLOUT_OFFSET - width
+        };
+        let y = if above {
+            anchor.y - CALLOUT_OFFSET - CALLOUT_HEIGHT
+        } else {
+            anchor.y + CALLOUT_OFFSET
+        };
+
+        // Boxes wider or taller than the room on both sides are kept inside the view from the
+        // left or the top
+        let x = x.min(view.max.x - width).max(view.min.x);
+        let y = y.min(view.max.y - CALLOUT_HEIGHT).max(view.min.y);
+        let rect = Rect::new(x, y, x + width, y + CALLOUT_HEIGHT);
+
+        // The leader line ends at the box corner closest to the value
+        let leader_end = Point::new(
+            anchor.x.clamp(rect.min.x, rect.max.x),
+            anchor.y.clamp(rect.min.y, rect.max.y),
+        );
+
+        ViewCallout {
+            anchor,
+            rect,
+            leader_end,
+            text: self.text.clone(),
+            class: self.class.clone(),
+        }
+    }
+}
+
+// A callout mapped to the view
+#[derive(Clone, PartialEq)]
+pub(crate) struct ViewCallout {
+    anchor: Point,
+    rect: Rect,
+    leader_end: Point,
+    text: String,
+    class: Option<String>,
+}
+
+pub(crate) fn callouts_element(callouts: &[ViewCallout], class: &str) -> Element {
+    let element_class = |callout: &ViewCallout, suffix: &str| match &callout.class {
+        Some(extra) => format!("{class}-{suffix} {extra}"),
+        None => format!("{class}-{suffix}"),
+    };
+
+    rsx! {
+        g {
+            class: "{class}",
+            for callout in callouts.iter() {
+                line {
+                    x1: "{callout.anchor.x}",
+                    y1: "{callout.anchor.y}",
+                    x2: "{callout.leader_end.x}",
+                    y2: "{callout.leader_end.y}",
+                    class: element_class(callout, "line"),
+                    stroke: "rgb(40, 40, 40)",
+                }
+                circle {
+                    cx: "{callout.anchor.x}",
+                    cy: "{callout.anchor.y}",
+                    r: "3",
+                    class: element_class(callout, "point"),
+                    fill: "rgb(40, 40, 40)",
+                }
+                rect {
+                    x: "{callout.rect.min.x}",
+                    y: "{callout.rect.min.y}",
+                    width: "{callout.rect.width()}",
+                    height: "{callout.rect.height()}",
+                    rx: "3",
+                    class: element_class(callout, "box"),
+                    fill: "white",
+                    stroke: "rgb(40, 40, 40)",
+                }
+                text {
+                    dx: format_args!("{}", callout.rect.min.x + callout.rect.width() / 2.0),
+                    dy: format_args!("{}", callout.rect.min.y + callout.rect.height() / 2.0),
+                    text_anchor: "middle",
+                    alignment_baseline: "middle",
+                    class: element_class(callout, "label"),
+                    "{callout.text}"
+                }
+            }
+        }
+    }
+}
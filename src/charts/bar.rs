@@ -1,13 +1,31 @@
 use dioxus::prelude::*;
 
+use crate::charts::annotation::{annotations_element, Annotation, ViewAnnotation};
+use crate::charts::style::{use_defs_prefix, ErrorBars, SeriesStyle, Threshold};
+use crate::error::*;
 use crate::grid::{Axis, Grid};
 use crate::types::*;
+use crate::utils::{text_width, view_length, TEXT_HEIGHT};
+
+/// The placement of the value labels along the bars.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BarLabelPosition {
+    /// Past the end of the bar.
+    OutsideEnd,
+    /// Inside the bar, at its end.
+    InsideEnd,
+    /// Inside the bar, at its center.
+    InsideCenter,
+    /// Inside the bar, at its base.
+    InsideBase,
+}
 
 /// The `BarChart` properties struct for the configuration of the bar chart.
 #[allow(clippy::struct_excessive_bools)]
+#[allow(unpredictable_function_pointer_comparisons)]
 #[derive(Clone, PartialEq, Props)]
 pub struct BarChartProps {
-    series: Series,
+    series: ReadSignal<Series>,
     #[props(optional)]
     labels: Option<Labels>,
 
@@ -51,11 +69,25 @@ pub struct BarChartProps {
     label_size: i32,
     #[props(optional)]
     label_interpolation: Option<fn(f32) -> String>,
+    #[props(optional)]
+    max_label_count: Option<usize>,
+    #[props(default = BarLabelPosition::OutsideEnd)]
+    bar_label_position: BarLabelPosition,
 
-    #[props(default = "5%".to_string(), into)]
-    bar_width: String,
-    #[props(default = 30.0)]
-    bar_distance: f32,
+    #[props(default = 0.1)]
+    bar_padding_inner: f32,
+    #[props(default = 0.2)]
+    bar_padding_outer: f32,
+    #[props(default)]
+    bar_radius: f32,
+    #[props(optional)]
+    series_styles: Option<Vec<SeriesStyle>>,
+    #[props(optional)]
+    thresholds: Option<Vec<Threshold>>,
+    #[props(optional)]
+    error_bars: Option<Vec<ErrorBars>>,
+    #[props(optional)]
+    annotations: Option<Vec<Annotation>>,
     #[props(default = false)]
     horizontal_bars: bool,
     #[props(default = false)]
@@ -69,6 +101,10 @@ pub struct BarChartProps {
     class_bar_group: String,
     #[props(default = "dx-bar-label".to_string(), into)]
     class_bar_label: String,
+    #[props(default = "dx-error-bar".to_string(), into)]
+    class_error_bar: String,
+    #[props(default = "dx-annotation".to_string(), into)]
+    class_annotation: String,
     #[props(default = "dx-grid".to_string(), into)]
     class_grid: String,
     #[props(default = "dx-grid-line".to_string(), into)]
@@ -77,6 +113,577 @@ pub struct BarChartProps {
     class_grid_label: String,
     #[props(default = "dx-grid-labels".to_string(), into)]
     class_grid_labels: String,
+
+    #[props(optional)]
+    onerror: Option<EventHandler<ChartError>>,
+    #[props(optional)]
+    fallback: Option<Element>,
+}
+
+impl BarChartProps {
+    fn geometry(&self) -> BarGeometry {
+        BarGeometry {
+            series: self.series,
+            labels: self.labels.clone(),
+            viewbox_width: self.viewbox_width,
+            viewbox_height: self.viewbox_height,
+            padding_top: self.padding_top,
+            padding_bottom: self.padding_bottom,
+            padding_left: self.padding_left,
+            padding_right: self.padding_right,
+            lowest: self.lowest,
+            highest: self.highest,
+            max_ticks: self.max_ticks,
+            show_grid_ticks: self.show_grid_ticks,
+            label_size: self.label_size,
+            label_interpolation: self.label_interpolation,
+            bar_padding_inner: self.bar_padding_inner,
+            bar_padding_outer: self.bar_padding_outer,
+            bar_widths: self
+                .series_styles
+                .iter()
+                .flatten()
+                .map(|style| style.stroke_width.clone())
+                .collect(),
+            error_bars: self.error_bars.clone(),
+            annotations: self.annotations.clone(),
+            horizontal_bars: self.horizontal_bars,
+            stacked_bars: self.stacked_bars,
+        }
+    }
+
+    // The `rect` of a bar, or a `path` when its outer end has rounded corners
+    fn bar_element(&self, bar: &Bar, fill: String) -> Element {
+        let thickness = bar.thickness;
+        let radius = if bar.outer {
+            self.bar_radius.min(thickness / 2.0).min(bar.length())
+        } else {
+            0.0
+        };
+
+        if radius > 0.0 {
+            rsx! {
+                path {
+                    d: bar.commands(radius, self.horizontal_bars),
+                    class: "{self.class_bar}",
+                    fill: "{fill}",
+                }
+            }
+        } else {
+            let bounds = bar.bounds(self.horizontal_bars);
+
+            rsx! {
+                rect {
+                    x: "{bounds.min.x}",
+                    y: "{bounds.min.y}",
+                    width: "{bounds.width()}",
+                    height: "{bounds.height()}",
+                    class: "{self.class_bar}",
+                    fill: "{fill}",
+                }
+            }
+        }
+    }
+
+    // The color of a bar with `value`, from the thresholds or the series `color`
+    fn bar_color(&self, value: f32, color: &str) -> String {
+        self.thresholds
+            .as_deref()
+            .and_then(|thresholds| Threshold::color(thresholds, value))
+            .unwrap_or(color)
+            .to_string()
+    }
+
+    // The whiskers of the error bars of a series, with caps half as wide as the bars
+    fn error_commands(&self, bars: &[Option<Bar>]) -> String {
+        bars.iter()
+            .flatten()
+            .filter_map(|bar| bar.error.map(|error| (error, bar.thickness / 2.0)))
+            .map(|((low, high), cap)| ErrorBars::commands(low, high, cap))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    // The value label of a bar with `value`
+    fn bar_label(&self, value: f32) -> String {
+        if let Some(func) = self.label_interpolation {
+            func(value)
+        } else {
+            format!("{value}")
+        }
+    }
+
+    // The placement of the `label` of a bar and whether it is inside the bar. Labels that don't fit
+    // inside the bar are moved past its end, or dropped for the `segment` of a stacked bar, which
+    // always has its label inside.
+    fn label_data(
+        &self,
+        bar: &Bar,
+        label: &str,
+        position: BarLabelPosition,
+        segment: bool,
+    ) -> Option<(TextData, bool)> {
+        let horizontal = self.horizontal_bars;
+        let (base, tip, center) = if horizontal {
+            (bar.rect.min.x, bar.rect.max.x, bar.rect.min.y)
+        } else {
+            (bar.rect.min.y, bar.rect.max.y, bar.rect.min.x)
+        };
+        // The view direction from the base to the tip of the bar
+        let direction = if tip < base { -1.0 } else { 1.0 };
+
+        let size = if horizontal {
+            text_width(label)
+        } else {
+            TEXT_HEIGHT
+        };
+        let fits = bar.length() >= size + 2.0 * BAR_LABEL_OFFSET;
+        let position = match position {
+            BarLabelPosition::OutsideEnd if segment && fits => BarLabelPosition::InsideCenter,
+            _ if fits => position,
+            _ if segment => return None,
+            _ => BarLabelPosition::OutsideEnd,
+        };
+
+        // The label coordinate along the bar, and whether the text extends towards the tip
+        let (along, forward) = match position {
+            BarLabelPosition::OutsideEnd => (tip + direction * BAR_LABEL_OFFSET, true),
+            BarLabelPosition::InsideEnd => (tip - direction * BAR_LABEL_OFFSET, false),
+            BarLabelPosition::InsideCenter => ((base + tip) / 2.0, true),
+            BarLabelPosition::InsideBase => (base + direction * BAR_LABEL_OFFSET, true),
+        };
+        // Up in the view for vertical bars, or right for horizontal bars
+        let increasing = (direction > 0.0) == forward;
+
+        let text = match (position, horizontal) {
+            (BarLabelPosition::InsideCenter, true) => TextData {
+                x: along,
+                y: center,
+                anchor: "middle",
+                baseline: "middle",
+            },
+            (BarLabelPosition::InsideCenter, false) => TextData {
+                x: center,
+                y: along,
+                anchor: "middle",
+                baseline: "middle",
+            },
+            (_, true) => TextData {
+                x: along,
+                y: center,
+                anchor: if increasing { "start" } else { "end" },
+                baseline: "middle",
+            },
+            (_, false) => TextData {
+                x: center,
+                y: along,
+                anchor: "middle",
+                baseline: if increasing { "hanging" } else { "text-bottom" },
+            },
+        };
+
+        Some((text, position != BarLabelPosition::OutsideEnd))
+    }
+
+    // The value label element of a bar, if it has a place
+    fn label_element(
+        &self,
+        bar: &Bar,
+        label: String,
+        position: BarLabelPosition,
+        segment: bool,
+    ) -> Element {
+        let Some((text, inside)) = self.label_data(bar, &label, position, segment) else {
+            return VNode::empty();
+        };
+        let class = if inside {
+            format!("{0} {0}-inside", self.class_bar_label)
+        } else {
+            self.class_bar_label.clone()
+        };
+
+        rsx! {
+            text {
+                dx: "{text.x}",
+                dy: "{text.y}",
+                text_anchor: "{text.anchor}",
+                class: "{class}",
+                alignment_baseline: "{text.baseline}",
+                "{label}"
+            }
+        }
+    }
+
+    // The labels with the total of every stack past its end
+    fn stack_totals(&self, bars: &[Vec<Option<Bar>>]) -> Vec<(Bar, String)> {
+        let categories = bars.iter().map(Vec::len).max().unwrap_or(0);
+
+        (0..categories)
+            .filter_map(|i| {
+                let segments = bars
+                    .iter()
+                    .filter_map(|bars| bars.get(i).copied().flatten());
+                let outer = segments.clone().find(|bar| bar.outer)?;
+                let total = segments.map(|bar| bar.value).sum::<f32>();
+
+                Some((outer, self.bar_label(total)))
+            })
+            .collect()
+    }
+}
+
+// The props of a `BarChart` the grid and the bars depend on, see `LineGeometry`
+#[allow(unpredictable_function_pointer_comparisons)]
+#[derive(Clone, PartialEq)]
+struct BarGeometry {
+    series: ReadSignal<Series>,
+    labels: Option<Labels>,
+    viewbox_width: i32,
+    viewbox_height: i32,
+    padding_top: i32,
+    padding_bottom: i32,
+    padding_left: i32,
+    padding_right: i32,
+    lowest: Option<f32>,
+    highest: Option<f32>,
+    max_ticks: i32,
+    show_grid_ticks: bool,
+    label_size: i32,
+    label_interpolation: Option<fn(f32) -> String>,
+    bar_padding_inner: f32,
+    bar_padding_outer: f32,
+    bar_widths: Vec<Option<String>>,
+    error_bars: Option<Vec<ErrorBars>>,
+    annotations: Option<Vec<Annotation>>,
+    horizontal_bars: bool,
+    stacked_bars: bool,
+}
+
+impl BarGeometry {
+    // The distance between the centers of the bars side by side and the width of each bar, from
+    // the category band minus the outer padding, split between the series minus the inner padding
+    fn band_layout(&self, axis_label: &Axis, series_count: usize) -> (f32, f32) {
+        let group = axis_label.band_width() * (1.0 - self.bar_padding_outer.clamp(0.0, 1.0));
+        let count = if self.stacked_bars {
+            1
+        } else {
+            series_count.max(1)
+        };
+        let slot = group / count as f32;
+
+        (slot, slot * (1.0 - self.bar_padding_inner.clamp(0.0, 1.0)))
+    }
+
+    // The width of the bars of the series at `index`, from its style or else, also when it can't be
+    // parsed, the band layout
+    fn thickness(&self, index: usize, band_thickness: f32) -> f32 {
+        self.bar_widths
+            .get(index)
+            .and_then(Option::as_deref)
+            .map_or(band_thickness, |width| {
+                view_length(
+                    width,
+                    band_thickness,
+                    self.viewbox_width,
+                    self.viewbox_height,
+                )
+            })
+    }
+
+    fn validate(&self, series: &Series) -> Result<(), ChartError> {
+        validate_viewbox(self.viewbox_width, self.viewbox_height)?;
+        validate_padding(
+            self.viewbox_width,
+            self.viewbox_height,
+            self.padding_top,
+            self.padding_bottom,
+            self.padding_left,
+            self.padding_right,
+        )?;
+        validate_range(Some(self.lowest.unwrap_or(0.0)), self.highest)?;
+        validate_series(series)
+    }
+
+    fn grid(&self) -> Result<Grid, ChartError> {
+        let series = self.series.read();
+        self.validate(&series)?;
+
+        let view = Rect::new(
+            self.padding_left as f32,
+            self.padding_top as f32,
+            (self.viewbox_width - self.padding_right) as f32,
+            (self.viewbox_height - self.padding_bottom) as f32,
+        );
+
+        let lowest = self.lowest.unwrap_or(0.0);
+
+        let max_ticks = self.max_ticks.max(3);
+
+        let mut domain_values = if self.stacked_bars {
+            ErrorBars::stacked_domain_values(self.error_bars.as_ref(), &series)
+        } else {
+            ErrorBars::domain_values(self.error_bars.as_ref(), &series)
+        };
+        domain_values.extend(Annotation::domain_values(self.annotations.as_ref()));
+
+        let axis_x = Axis::builder()
+            .with_view(view)
+            .with_grid_ticks(self.show_grid_ticks)
+            .with_label_size(self.label_size)
+            .with_category_count(series.iter().map(Vec::len).max().unwrap_or(0))
+            .with_centered_labels(self.labels.as_ref());
+
+        let axis_y = Axis::builder()
+            .with_view(view)
+            .with_max_ticks(max_ticks)
+            .with_grid_ticks(self.show_grid_ticks)
+            .with_series(&series)
+            .with_stacked_series(self.stacked_bars)
+            .with_domain_values(&domain_values)
+            .with_label_interpolation(self.label_interpolation)
+            .with_highest(self.highest)
+            .with_lowest(Some(lowest));
+
+        if self.horizontal_bars {
+            Ok(Grid::new(axis_y, axis_x))
+        } else {
+            Ok(Grid::new(axis_x, axis_y))
+        }
+    }
+
+    fn axes<'a>(&self, grid: &'a Grid) -> (&'a Axis, &'a Axis) {
+        if self.horizontal_bars {
+            (&grid.x, &grid.y)
+        } else {
+            (&grid.y, &grid.x)
+        }
+    }
+
+    fn annotations(&self, grid: &Grid) -> Vec<ViewAnnotation> {
+        let (axis_value, axis_label) = self.axes(grid);
+
+        self.annotations
+            .iter()
+            .flatten()
+            .map(|annotation| {
+                annotation.to_view(
+                    axis_value.view(),
+                    |value| axis_value.world_to_view(value, 0.0),
+                    |position| axis_label.category_center(position),
+                    self.horizontal_bars,
+                )
+            })
+            .collect()
+    }
+
+    // The view geometry of every bar, per series and category, with `None` for gaps.
+    fn bars(&self, grid: &Grid) -> Vec<Vec<Option<Bar>>> {
+        let series = self.series.read();
+        let (axis_value, axis_label) = self.axes(grid);
+        let tick_centers = axis_label.tick_centers();
+        let (slot, band_thickness) = self.band_layout(axis_label, series.len());
+
+        // The whisker ends of the error bar of a bar ending at the `end` value and at `tip` on
+        // the view
+        let error = |series: usize, index: usize, end: f32, tip: Point| {
+            ErrorBars::get(self.error_bars.as_ref(), series, index).map(|(minus, plus)| {
+                let low = axis_value.world_to_view(end - minus, 0.0);
+                let high = axis_value.world_to_view(end + plus, 0.0);
+
+                if self.horizontal_bars {
+                    (Point::new(low, tip.y), Point::new(high, tip.y))
+                } else {
+                    (Point::new(tip.x, low), Point::new(tip.x, high))
+                }
+            })
+        };
+
+        if self.stacked_bars {
+            let mut all_series_bars = Vec::<Vec<Option<Bar>>>::new();
+            let mut last_bar_ends: Option<Vec<f32>> = None;
+
+            for (series_index, a) in series.iter().enumerate() {
+                let thickness = self.thickness(series_index, band_thickness);
+                let mut bars = Vec::<Option<Bar>>::new();
+                let mut view_bar_ends = Vec::<f32>::new();
+
+                for (point, (i, v)) in tick_centers.iter().zip(a.iter().enumerate()) {
+                    // Gaps don't get a segment, but the stack end is carried over so the next
+                    // series still starts where the previous ones finished
+                    if !v.is_finite() {
+                        let last_end = last_bar_ends
+                            .as_ref()
+                            .map_or(0.0, |bar_ends| bar_ends.get(i).copied().unwrap_or(0.0));
+                        view_bar_ends.push(last_end);
+                        bars.push(None);
+                        continue;
+                    }
+
+                    let rect = if let Some(bar_ends) = &last_bar_ends {
+                        // Shorter previous series leave nothing to stack on
+                        let last_end = bar_ends.get(i).copied().unwrap_or(0.0);
+                        let end = axis_value.world_to_view(v + last_end, 0.0);
+                        view_bar_ends.push(v + last_end);
+
+                        let last_end = axis_value.world_to_view(last_end, 0.0);
+
+                        if self.horizontal_bars {
+                            Rect::new(last_end, point.y, end, point.y)
+                        } else {
+                            Rect::new(point.x, last_end, point.x, end)
+                        }
+                    } else {
+                        let end = axis_value.world_to_view(*v, 0.0);
+                        view_bar_ends.push(*v);
+
+                        if self.horizontal_bars {
+                            Rect::new(point.x, point.y, end, point.y)
+                        } else {
+                            Rect::new(point.x, point.y, point.x, end)
+                        }
+                    };
+
+                    let end = view_bar_ends[i];
+                    bars.push(Some(Bar {
+                        rect,
+                        value: *v,
+                        error: error(series_index, i, end, rect.max),
+                        thickness,
+                        outer: false,
+                    }));
+                }
+
+                // Keep the stack ends of the categories a shorter series doesn't reach
+                if let Some(bar_ends) = &last_bar_ends {
+                    view_bar_ends.extend(bar_ends.iter().skip(view_bar_ends.len()));
+                }
+
+                all_series_bars.push(bars);
+                last_bar_ends = Some(view_bar_ends);
+            }
+
+            // Only the last segment of every stack gets the outer end
+            let categories = all_series_bars.iter().map(Vec::len).max().unwrap_or(0);
+            for i in 0..categories {
+                if let Some(bar) = all_series_bars
+                    .iter_mut()
+                    .rev()
+                    .find_map(|bars| bars.get_mut(i).and_then(Option::as_mut))
+                {
+                    bar.outer = true;
+                }
+            }
+
+            all_series_bars
+        } else {
+            series
+                .iter()
+                .enumerate()
+                .map(|(i, a)| {
+                    let offset = (i as f32 - (series.len() as f32 - 1.0) / 2.0) * slot;
+                    let thickness = self.thickness(i, band_thickness);
+
+                    tick_centers
+                        .iter()
+                        .zip(a.iter().enumerate())
+                        .map(|(point, (j, v))| {
+                            v.is_finite().then(|| {
+                                let end = axis_value.world_to_view(*v, 0.0);
+                                let rect = if self.horizontal_bars {
+                                    Rect::new(point.x, point.y + offset, end, point.y + offset)
+                                } else {
+                                    Rect::new(point.x + offset, point.y, point.x + offset, end)
+                                };
+
+                                Bar {
+                                    rect,
+                                    value: *v,
+                                    error: error(i, j, *v, rect.max),
+                                    thickness,
+                                    outer: true,
+                                }
+                            })
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+    }
+}
+
+// The distance of the value labels from the bar ends, in view units
+const BAR_LABEL_OFFSET: f32 = 5.0;
+
+// A bar with the `rect` line from the base to the tip along its center
+#[derive(Clone, Copy, PartialEq)]
+struct Bar {
+    rect: Rect,
+    value: f32,
+    error: Option<(Point, Point)>,
+    thickness: f32,
+    // Whether this is the outer end of the bar or of its stack, which gets the rounded corners
+    outer: bool,
+}
+
+impl Bar {
+    fn length(&self) -> f32 {
+        self.rect.width().abs() + self.rect.height().abs()
+    }
+
+    // The rectangle of the bar with its thickness across the center line
+    fn bounds(&self, horizontal: bool) -> Rect {
+        let thickness = self.thickness;
+        let (min, max) = (self.rect.min, self.rect.max);
+
+        if horizontal {
+            Rect::new(
+                min.x.min(max.x),
+                min.y - thickness / 2.0,
+                min.x.max(max.x),
+                min.y + thickness / 2.0,
+            )
+        } else {
+            Rect::new(
+                min.x - thickness / 2.0,
+                min.y.min(max.y),
+                min.x + thickness / 2.0,
+                min.y.max(max.y),
+            )
+        }
+    }
+
+    // The path of the bar with the corners at its tip rounded by `radius`
+    fn commands(&self, radius: f32, horizontal: bool) -> String {
+        let thickness = self.thickness;
+        // Along the value axis and across it, mapped back to view points
+        let point = |along: f32, across: f32| {
+            if horizontal {
+                Point::new(along, across)
+            } else {
+                Point::new(across, along)
+            }
+        };
+        let (base, tip, center) = if horizontal {
+            (self.rect.min.x, self.rect.max.x, self.rect.min.y)
+        } else {
+            (self.rect.min.y, self.rect.max.y, self.rect.min.x)
+        };
+        let direction = if tip < base { -1.0 } else { 1.0 };
+        // Swapping the axes mirrors the path, which turns the arcs the other way
+        let sweep = i32::from((direction < 0.0) != horizontal);
+        let (side_a, side_b) = (center - thickness / 2.0, center + thickness / 2.0);
+        let corner = tip - direction * radius;
+
+        format!(
+            "M{} L{} A{radius},{radius},0,0,{sweep},{} L{} A{radius},{radius},0,0,{sweep},{} L{} Z",
+            point(base, side_a),
+            point(corner, side_a),
+            point(tip, side_a + radius),
+            point(tip, side_b - radius),
+            point(corner, side_b),
+            point(base, side_b),
+        )
+    }
 }
 
 /// This is the `BarChart` function used to render the bar chart `Element`.
@@ -96,7 +703,7 @@ pub struct BarChartProps {
 ///             padding_left: 70,
 ///             padding_right: 50,
 ///             padding_bottom: 30,
-///             bar_width: "10%",
+///             bar_padding_outer: 0.3,
 ///             horizontal_bars: true,
 ///             label_interpolation: (|v| format!("{v:.1}%")) as fn(f32) -> String,
 ///             series: vec![
@@ -111,13 +718,16 @@ pub struct BarChartProps {
 /// # Props
 ///
 /// - `series`: [Vec]<[Vec]<[f32]>> (**required**): The series vector of vectors with the all series values.
+///   It also accepts a `Signal`, `Memo` or `ReadSignal` of it, so live data only rebuilds the
+///   chart geometry when the values change.
+///   Missing values can be set as `f32::NAN` to skip their bars.
 /// - `labels`: [Vec]<[String]> (optional): Optional labels to show on the labels axis.
 /// ---
 /// - `width`: &[str] (default: `"100%"`): The SVG element width attribute. It also accepts any
-/// other CSS style, i.e., "200px"
+///   other CSS style, i.e., "200px"
 /// - `height`: &[str] (default: `"100%"`): The SVG height counter-part of the `width` prop above.
 /// - `viewbox_width`: [i32] (default: `600`): The SVG viewbox width. Together with
-/// `viewbox_height` it is useful for adjusting the aspect ratio for longer charts.
+///   `viewbox_height` it is useful for adjusting the aspect ratio for longer charts.
 /// - `viewbox_height`: [i32] (default: `400`): The SVG viewbox height.
 /// ---
 /// - `padding_top`: [i32] (default: `0`): Padding for the top side of the view box.
@@ -132,89 +742,89 @@ pub struct BarChartProps {
 /// - `show_grid`: [bool] (default: `true`): Show/hide the chart grid.
 /// - `show_dotted_grid`: [bool] (default: `true`): Show the chart grid with dotted style or not.
 /// - `show_grid_ticks`: [bool] (default: `false`): Show the chart grid ticks instead of drawing the
-/// whole grid lines for a cleaner look.
+///   whole grid lines for a cleaner look.
 /// - `show_labels`: [bool] (default: `true`): Show/hide the labels.
-/// - `show_series_labels`: [bool] (default: `true`): Show/hide the values labels at the top of
-/// bars.
+/// - `show_series_labels`: [bool] (default: `true`): Show/hide the values labels of the bars, or
+///   of the segments and the totals of stacked bars.
 /// ---
 /// - `label_size`: [i32] (default: `60`): The maximum width or height of the label rect depending
-/// on whether the chart shows horizontal or vertical bars.
+///   on whether the chart shows horizontal or vertical bars.
 /// - `label_interpolation`: fn([f32]) -> [String] (optional): Function for formatting the
-/// generated labels for values.
+///   generated labels for values.
+/// - `max_label_count`: [usize] (optional): The maximum number of category labels shown. Labels
+///   that would overlap are also rotated by 45° or 90°, or thinned out to every nth one, based on
+///   their estimated text width.
+/// - `bar_label_position`: [`BarLabelPosition`] (default: [`BarLabelPosition::OutsideEnd`]): The
+///   placement of the value labels along the bars. Labels that don't fit inside a bar are placed
+///   past its end instead. Stacked segments show their labels inside, at the center for
+///   `OutsideEnd`, or none if they don't fit, and the stack totals are placed past the ends.
 /// ---
-/// - `bar_width`: &[str] (default: `"5%"`): The width of each bar.
-/// - `bar_distance`: [f32] (default: `30.0`): The distance between the bars for charts that have
-/// multiple ones side by side.
+/// - `bar_padding_inner`: [f32] (default: `0.1`): The ratio of the space of each bar left empty
+///   between the bars side by side in a category, from `0.0` to `1.0`.
+/// - `bar_padding_outer`: [f32] (default: `0.2`): The ratio of the category band left empty
+///   between the groups of bars of neighbouring categories, from `0.0` to `1.0`. The bars of a
+///   category are sized to fit in the rest of the band.
+/// - `bar_radius`: [f32] (default: `0.0`): The radius of the rounded corners at the outer end of
+///   the bars, or only of the last segment of stacked bars. It is limited to half the bar width.
+/// - `series_styles`: [Vec]<[`SeriesStyle`]> (optional): The style of the bars of each series,
+///   overriding the computed bar width and the default colors. The line style, marker and fill are
+///   not used.
+/// - `thresholds`: [Vec]<[`Threshold`]> (optional): Rules coloring the bars by value.
+/// - `error_bars`: [Vec]<[`ErrorBars`]> (optional): Error bars drawn around the ends of the bars,
+///   or of the stack segments for stacked bars. They are included in the value axis.
+/// - `annotations`: [Vec]<[`Annotation`]> (optional): Reference lines, shaded ranges and texts
+///   drawn over the chart. Positions on the labels axis are the indices of the bar categories.
 /// - `horizontal_bars`: [bool] (default: `false`): Show horizontal bars.
 /// - `stacked_bars`: [bool] (default: `false`): Build a Stacked Bars chart.
 /// ---
 /// - `class_chart_bar`: &[str] (default: `"dx-chart-line"`): The HTML element `class` of the
-/// chart.
+///   chart.
 /// - `class_bar`: &[str] (default: `"dx-bar"`): The HTML element `class` of the whole line.
 /// - `class_bar_group`: &[str] (default: `"dx-bar-group"`): The HTML element `class` of the line path.
-/// - `class_bar_label`: &[str] (default: `"dx-bar-label"`): The HTML element `class` of the line
-/// labels.
+/// - `class_bar_label`: &[str] (default: `"dx-bar-label"`): The HTML element `class` of the value
+///   labels, with the extra `-inside` class for labels inside the bars and the `-totals` class for
+///   the group of stack totals.
+/// - `class_error_bar`: &[str] (default: `"dx-error-bar"`): The HTML element `class` of the error
+///   bars of a series.
+/// - `class_annotation`: &[str] (default: `"dx-annotation"`): The HTML element `class` of the
+///   annotations group, which is also the prefix of the `-line`, `-range` and `-label` classes of
+///   the annotation elements.
 /// - `class_grid`: &[str] (default: `"dx-grid"`): The HTML element `class` of the grid.
 /// - `class_grid_line`: &[str] (default: `"dx-grid-line"`): The HTML element `class` of every grid
-/// line.
+///   line.
 /// - `class_grid_label`: &[str] (default: `"dx-grid-label"`): The HTML element `class` of the grid
-/// labels.
+///   labels.
 /// - `class_grid_labels`: &[str] (default: `"dx-grid-labels"`): The HTML element `class` of the
-/// group of grid labels.
+///   group of grid labels.
+/// ---
+/// - `onerror`: [EventHandler]<[`ChartError`]> (optional): Called with the error when the props
+///   fail validation and the chart can't be drawn.
+/// - `fallback`: [Element] (optional): The element rendered instead of the chart on errors. A short
+///   error message is shown by default.
 #[allow(non_snake_case)]
 pub fn BarChart(props: BarChartProps) -> Element {
-    for series in props.series.iter() {
-        if series.is_empty() {
-            return rsx!("Bar chart error: empty series");
-        }
-    }
-
-    let grid = {
-        let view = Rect::new(
-            props.padding_left as f32,
-            props.padding_top as f32,
-            (props.viewbox_width - props.padding_right) as f32,
-            (props.viewbox_height - props.padding_bottom) as f32,
-        );
+    // Only rebuilt when the geometry props or the series values change, see `LineChart`
+    let geometry = props.geometry();
+    let grid = use_memo(use_reactive((&geometry,), |(geometry,)| geometry.grid()));
+    let bars = use_memo(use_reactive(
+        (&geometry,),
+        move |(geometry,)| match &*grid.read() {
+            Ok(grid) => geometry.bars(grid),
+            Err(_) => Vec::new(),
+        },
+    ));
 
-        let lowest = if let Some(low) = props.lowest {
-            low
-        } else {
-            0.0
-        };
+    let defs_prefix = use_defs_prefix();
 
-        let max_ticks = props.max_ticks.max(3);
-
-        let axis_x = Axis::builder()
-            .with_view(view)
-            .with_grid_ticks(props.show_grid_ticks)
-            .with_label_size(props.label_size)
-            .with_centered_labels(props.labels.as_ref());
-
-        let axis_y = Axis::builder()
-            .with_view(view)
-            .with_max_ticks(max_ticks)
-            .with_grid_ticks(props.show_grid_ticks)
-            .with_series(&props.series)
-            .with_stacked_series(props.stacked_bars)
-            .with_label_interpolation(props.label_interpolation)
-            .with_highest(props.highest)
-            .with_lowest(Some(lowest));
-
-        if props.horizontal_bars {
-            Grid::new(axis_y, axis_x)
-        } else {
-            Grid::new(axis_x, axis_y)
-        }
+    let grid = match use_validation(grid(), props.onerror) {
+        Ok(grid) => grid,
+        Err(error) => return error_element("Bar chart", &error, props.fallback),
     };
 
-    let (axis_value, axis_label) = if props.horizontal_bars {
-        (&grid.x, &grid.y)
-    } else {
-        (&grid.y, &grid.x)
-    };
+    let (axis_value, axis_label) = geometry.axes(&grid);
 
     let lines = grid.lines();
+    let annotations = geometry.annotations(&grid);
 
     let mut color_var = 255.0;
     let dotted_stroke = if props.show_dotted_grid {
@@ -236,156 +846,145 @@ pub fn BarChart(props: BarChartProps) -> Element {
         None
     };
 
-    let grid_centered_labels = if props.show_labels {
-        let rects = axis_label
-            .centered_text_rects(props.labels.as_ref().unwrap().len() as i32)
-            .into_iter();
+    let label_layout = props
+        .labels
+        .as_ref()
+        .map(|labels| axis_label.label_layout(labels, props.max_label_count));
 
-        let labels = if props.horizontal_bars {
-            rects
-                .zip(props.labels.as_ref().unwrap().iter().rev())
-                .collect::<Vec<(Rect, &String)>>()
-        } else {
-            rects
-                .zip(props.labels.as_ref().unwrap().iter())
-                .collect::<Vec<(Rect, &String)>>()
-        };
+    // Flat labels fill the category rects, while rotated ones are anchored below the categories
+    let grid_centered_labels = match (props.labels.as_ref(), label_layout) {
+        (Some(labels), Some(layout)) if props.show_labels && !layout.is_rotated() => {
+            let rects = axis_label
+                .centered_text_rects(labels.len() as i32)
+                .into_iter();
 
-        Some(labels)
-    } else {
-        None
-    };
+            let labels = if props.horizontal_bars {
+                rects
+                    .zip(labels.iter().enumerate().rev())
+                    .filter(|(_, (i, _))| layout.shows(*i))
+                    .map(|(rect, (_, label))| (rect, label))
+                    .collect::<Vec<(Rect, &String)>>()
+            } else {
+                rects
+                    .zip(labels.iter().enumerate())
+                    .filter(|(_, (i, _))| layout.shows(*i))
+                    .map(|(rect, (_, label))| (rect, label))
+                    .collect::<Vec<(Rect, &String)>>()
+            };
 
-    let stacked_bars_rects = if props.stacked_bars {
-        let tick_centers = axis_label.tick_centers();
-        let mut all_series_rects = Vec::<Vec<Rect>>::new();
-        let mut last_bar_ends: Option<Vec<f32>> = None;
+            Some(labels)
+        }
+        _ => None,
+    };
 
-        for a in props.series.iter() {
-            let mut rects = Vec::<Rect>::new();
-            let mut view_bar_ends = Vec::<f32>::new();
+    let grid_rotated_labels = match (props.labels.as_ref(), label_layout) {
+        (Some(labels), Some(layout)) if props.show_labels && layout.is_rotated() => Some(
+            axis_label
+                .centered_text_data()
+                .into_iter()
+                .zip(labels.iter())
+                .enumerate()
+                .filter(|(i, _)| layout.shows(*i))
+                .map(|(_, (text, label))| {
+                    let text = layout.text(text);
+                    let transform = layout.transform(&text);
+                    (text, label, transform)
+                })
+                .collect::<Vec<(TextData, &String, Option<String>)>>(),
+        ),
+        _ => None,
+    };
 
-            for (point, (i, v)) in tick_centers.iter().zip(a.iter().enumerate()) {
-                let rect = if let Some(bar_ends) = &last_bar_ends {
-                    let last_end = bar_ends[i];
-                    let end = axis_value.world_to_view(v + last_end, 0.0);
-                    view_bar_ends.push(v + last_end);
+    let bars = bars.read();
+    let stacked_bars_rects = props.stacked_bars.then_some(&*bars);
 
-                    let last_end = axis_value.world_to_view(last_end, 0.0);
+    let stacked_bars_rects_rsx = stacked_bars_rects.map(|all_series_rects| {
+        let series_rsx = all_series_rects
+            .iter()
+            .enumerate()
+            .map(|(i, series_rects)| {
+                color_var -= 75.0 * (1.0 / (i + 1) as f32);
 
-                    if props.horizontal_bars {
-                        Rect::new(last_end, point.y, end, point.y)
-                    } else {
-                        Rect::new(point.x, last_end, point.x, end)
-                    }
-                } else {
-                    let end = axis_value.world_to_view(*v, 0.0);
-                    view_bar_ends.push(*v);
+                let style = SeriesStyle::get(props.series_styles.as_ref(), i);
+                let color = style.color(color_var);
+                let pattern = style.pattern_element(&defs_prefix, i, &color);
+                let color = style.paint(&defs_prefix, i).unwrap_or(color);
+                let error_commands = props.error_commands(series_rects);
 
-                    if props.horizontal_bars {
-                        Rect::new(point.x, point.y, end, point.y)
-                    } else {
-                        Rect::new(point.x, point.y, point.x, end)
+                rsx! {
+                    g {
+                        class: style.class(format!("{}-{i}", props.class_bar_group)),
+                        opacity: style.opacity,
+                        if let Some(pattern) = pattern {
+                            defs {
+                                {pattern}
+                            }
+                        }
+                        for bar in series_rects.iter().flatten() {
+                            {
+                                props.bar_element(bar, props.bar_color(bar.value, &color))
+                            }
+                            if props.show_series_labels {
+                                {
+                                    props.label_element(
+                                        bar,
+                                        props.bar_label(bar.value),
+                                        props.bar_label_position,
+                                        true,
+                                    )
+                                }
+                            }
+                        }
+                        if !error_commands.is_empty() {
+                            path {
+                                d: "{error_commands}",
+                                class: "{props.class_error_bar}",
+                                stroke: "rgb(40, 40, 40)",
+                                stroke_width: "0.3%",
+                                fill: "none",
+                            }
+                        }
                     }
-                };
-
-                rects.push(rect);
-            }
+                }
+            });
 
-            all_series_rects.push(rects);
-            last_bar_ends = Some(view_bar_ends);
-        }
+        let totals = props.stack_totals(all_series_rects);
 
-        Some(all_series_rects)
-    } else {
-        None
-    };
-
-    let stacked_bars_rects_rsx = stacked_bars_rects.map(|all_series_rects| {
         rsx! {
-            //all_series_rects.iter().enumerate().map(|(i, series_rects)| {
-            for (i, series_rects) in all_series_rects.iter().enumerate() {
-                {color_var -= 75.0 * (1.0 / (i + 1) as f32);}
-
+            {series_rsx}
+            if props.show_series_labels {
                 g {
-                    class: "{props.class_bar_group}-{i}",
-                    {
-                        series_rects.iter().map(|rect| {
-                            rsx! {
-                                line {
-                                    x1: "{rect.min.x}",
-                                    y1: "{rect.min.y}",
-                                    x2: "{rect.max.x}",
-                                    y2: "{rect.max.y}",
-                                    class: "{props.class_bar}",
-                                    stroke: "rgb({color_var}, 40, 40)",
-                                    stroke_width: "{props.bar_width}",
-                                }
-                            }
-                        })
+                    class: "{props.class_bar_label}-totals",
+                    for (bar, total) in totals {
+                        {props.label_element(&bar, total, BarLabelPosition::OutsideEnd, false)}
                     }
                 }
             }
         }
     });
 
-    let series_rsx = props.series.iter().enumerate().map(|(i, a)| {
+    let series_rsx = bars.iter().enumerate().map(|(i, series_bars)| {
         color_var -= 75.0 * (1.0 / (i + 1) as f32);
-        let offset = (i as f32 - (props.series.len() as f32 - 1.0) / 2.0) * props.bar_distance;
-        let tick_centers = axis_label.tick_centers();
 
-        let tick_centers_rsx = tick_centers.iter().zip(a.iter()).map(|(point, v)| {
-            let end = axis_value.world_to_view(*v, 0.0);
-            let (rect, text) = if props.horizontal_bars {
-                (
-                    Rect::new(point.x, point.y + offset, end, point.y + offset),
-                    TextData {
-                        x: end + 5.0,
-                        y: point.y + offset,
-                        anchor: "start",
-                        baseline: "middle",
-                    },
-                )
-            } else {
-                (
-                    Rect::new(point.x + offset, point.y, point.x + offset, end),
-                    TextData {
-                        x: point.x + offset,
-                        y: end - 5.0,
-                        anchor: "middle",
-                        baseline: "text-bottom",
-                    },
-                )
-            };
-
-            let bar_label = {
-                if !props.show_series_labels {
-                    String::new()
-                } else if let Some(func) = props.label_interpolation {
-                    func(*v)
-                } else {
-                    format!("{}", *v)
-                }
-            };
+        let style = SeriesStyle::get(props.series_styles.as_ref(), i);
+        let color = style.color(color_var);
+        let pattern = style.pattern_element(&defs_prefix, i, &color);
+        let color = style.paint(&defs_prefix, i).unwrap_or(color);
+        let error_commands = props.error_commands(series_bars);
 
+        let tick_centers_rsx = series_bars.iter().flatten().map(|bar| {
             rsx! {
-                line {
-                    x1: "{rect.min.x}",
-                    y1: "{rect.min.y}",
-                    x2: "{rect.max.x}",
-                    y2: "{rect.max.y}",
-                    class: "{props.class_bar}",
-                    stroke: "rgb({color_var}, 40, 40)",
-                    stroke_width: "{props.bar_width}",
-                },
+                {
+                    props.bar_element(bar, props.bar_color(bar.value, &color))
+                }
                 if props.show_series_labels {
-                    text {
-                        dx: "{text.x}",
-                        dy: "{text.y}",
-                        text_anchor: "{text.anchor}",
-                        class: "{props.class_bar_label}",
-                        alignment_baseline: "{text.baseline}",
-                        "{bar_label}"
+                    {
+                        props.label_element(
+                            bar,
+                            props.bar_label(bar.value),
+                            props.bar_label_position,
+                            false,
+                        )
                     }
                 },
             }
@@ -393,12 +992,42 @@ pub fn BarChart(props: BarChartProps) -> Element {
 
         rsx! {
             g {
-                class: "{props.class_bar_group}-{i}",
+                class: style.class(format!("{}-{i}", props.class_bar_group)),
+                opacity: style.opacity,
+                if let Some(pattern) = pattern {
+                    defs {
+                        {pattern}
+                    }
+                }
                 {tick_centers_rsx}
+                if !error_commands.is_empty() {
+                    path {
+                        d: "{error_commands}",
+                        class: "{props.class_error_bar}",
+                        stroke: "rgb(40, 40, 40)",
+                        stroke_width: "0.3%",
+                        fill: "none",
+                    }
+                }
             }
         }
     });
 
+    // Spread over the plot area so the colors follow the value axis across all the bars
+    let gradients = props
+        .series_styles
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(i, style)| {
+            style.gradient.as_ref().map(|gradient| {
+                gradient.element(
+                    format!("{defs_prefix}-gradient-{i}"),
+                    Some(axis_value.view()),
+                )
+            })
+        });
+
     rsx! {
         div {
             svg {
@@ -408,6 +1037,11 @@ pub fn BarChart(props: BarChartProps) -> Element {
                 class: "{props.class_chart_bar}",
                 preserve_aspect_ratio: "xMidYMid meet",
                 view_box: "0 0 {props.viewbox_width} {props.viewbox_height}",
+                defs {
+                    for gradient in gradients {
+                        {gradient}
+                    }
+                }
 
                 if props.show_grid {
                     g {
@@ -442,6 +1076,23 @@ pub fn BarChart(props: BarChartProps) -> Element {
                     }
                 },
 
+                for labels in grid_rotated_labels {
+                    g {
+                        class: "{props.class_grid_labels}",
+                        for (text, label, transform) in labels {
+                            text {
+                                dx: "{text.x}",
+                                dy: "{text.y}",
+                                text_anchor: "{text.anchor}",
+                                class: "{props.class_grid_label}",
+                                alignment_baseline: "{text.baseline}",
+                                transform,
+                                "{label}"
+                            }
+                        }
+                    }
+                },
+
                 for labels in grid_centered_labels {
                     g {
                         class: "{props.class_grid_labels}",
@@ -479,12 +1130,20 @@ pub fn BarChart(props: BarChartProps) -> Element {
                     }
                 },
 
+                if !annotations.is_empty() {
+                    {annotations_element(&annotations, &props.class_annotation, true)}
+                }
+
                 {stacked_bars_rects_rsx}
 
 
                 if !props.stacked_bars {
                     {series_rsx}
                 }
+
+                if !annotations.is_empty() {
+                    {annotations_element(&annotations, &props.class_annotation, false)}
+                }
             }
         }
     }
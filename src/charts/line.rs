@@ -1,13 +1,143 @@
+use std::collections::BTreeMap;
+
 use dioxus::prelude::*;
 
+use crate::charts::annotation::{
+    annotations_element, callouts_element, Annotation, Callout, ViewAnnotation, ViewCallout,
+};
+use crate::charts::style::{use_defs_prefix, ErrorBars, LineStyle, Marker, SeriesStyle, Threshold};
+use crate::error::*;
 use crate::grid::{Axis, Grid};
 use crate::types::*;
+use crate::utils::{lttb, min_max_buckets, relax_positions, view_length, TEXT_HEIGHT};
+
+/// The downsampling of series with more points than the chart can show.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Downsample {
+    /// To draw every point.
+    None,
+    /// To use the Largest-Triangle-Three-Buckets algorithm, which keeps the shape of the line.
+    Lttb,
+    /// To keep the lowest and highest points of every bucket, so no spike is lost.
+    MinMax,
+}
+
+impl Downsample {
+    // The smallest threshold the algorithm samples the points down to
+    fn min_threshold(self) -> usize {
+        match self {
+            Downsample::MinMax => 4,
+            _ => 3,
+        }
+    }
+
+    // Samples the values down to at most `threshold` points, sharing it between the segments
+    // around the gaps, which are kept as `None`. Every segment keeps its ends, and the rest of the
+    // threshold goes to the points in between. When there are too many segments for their ends to
+    // fit, only an evenly spread selection of them is kept.
+    fn apply(self, values: &[f32], threshold: usize) -> Vec<Option<(usize, f32)>> {
+        let finite = values.iter().filter(|v| v.is_finite()).count();
+
+        if self == Downsample::None || finite <= threshold {
+            return values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| v.is_finite().then_some((i, *v)))
+                .collect();
+        }
+
+        let points = values.iter().copied().enumerate().collect::<Vec<_>>();
+        let segments = points
+            .split(|(_, v)| !v.is_finite())
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+        let ends = segments.iter().map(|s| s.len().min(2)).sum::<usize>();
+        let inner = finite - ends;
+        let segment_ends = |segment: &[(usize, f32)]| {
+            let mut ends = segment[..1].to_vec();
+            ends.extend(segment.last().filter(|_| segment.len() > 1));
+            ends
+        };
+
+        let mut sampled = Vec::with_capacity(threshold + segments.len());
+        let mut push = |points: &[(usize, f32)]| {
+            sampled.extend(points.iter().copied().map(Some));
+            sampled.push(None);
+        };
+
+        if ends >= threshold {
+            let kept = threshold / 2;
+            for (k, segment) in segments.iter().enumerate() {
+                if (k * kept) % segments.len() < kept {
+                    push(&segment_ends(segment));
+                }
+            }
+        } else {
+            for segment in segments {
+                let kept_ends = segment.len().min(2);
+                let segment_threshold =
+                    kept_ends + (threshold - ends) * (segment.len() - kept_ends) / inner.max(1);
+
+                // Segments with too small a share of the threshold to be sampled only keep their ends
+                match self {
+                    _ if segment_threshold < self.min_threshold() => {
+                        push(&segment_ends(segment));
+                    }
+                    Downsample::MinMax => push(&min_max_buckets(segment, segment_threshold)),
+                    _ => push(&lttb(segment, segment_threshold)),
+                }
+            }
+        }
+
+        sampled
+    }
+}
+
+/// The lower and upper bounds of the values of a series, drawn as a shaded band beneath its line,
+/// i.e. for prediction intervals.
+#[derive(Clone, PartialEq)]
+pub struct Band {
+    series: usize,
+    lower: Vec<f32>,
+    upper: Vec<f32>,
+}
+
+impl Band {
+    /// Creates the band of the series at the `series` index, with a lower and an upper bound per
+    /// value. Values missing either bound (`f32::NAN`) break the band like gaps break the line.
+    pub fn new(series: usize, lower: Vec<f32>, upper: Vec<f32>) -> Self {
+        Self {
+            series,
+            lower,
+            upper,
+        }
+    }
+
+    // The bound values, with gaps where the other bound is missing
+    fn bound(values: &[f32], other: &[f32]) -> Vec<f32> {
+        values
+            .iter()
+            .zip(other)
+            .map(|(v, o)| if o.is_finite() { *v } else { f32::NAN })
+            .collect()
+    }
+}
+
+// A drawn series value and its position on the view
+#[derive(Clone, Copy, PartialEq)]
+struct SeriesPoint {
+    index: usize,
+    value: f32,
+    view: Point,
+    error: Option<(Point, Point)>,
+}
 
 /// The `LineChart` properties struct for the configuration of the line chart.
 #[allow(clippy::struct_excessive_bools)]
+#[allow(unpredictable_function_pointer_comparisons)]
 #[derive(Clone, PartialEq, Props)]
 pub struct LineChartProps {
-    series: Series,
+    series: ReadSignal<Series>,
     #[props(optional)]
     labels: Option<Labels>,
     #[props(optional)]
@@ -41,6 +171,8 @@ pub struct LineChartProps {
     show_labels: bool,
     #[props(default = true)]
     show_dots: bool,
+    #[props(default = false)]
+    interactive_dots: bool,
     #[props(default = true)]
     show_lines: bool,
     #[props(default = true)]
@@ -51,7 +183,26 @@ pub struct LineChartProps {
     #[props(default = "3%".to_string(), into)]
     dot_size: String,
     #[props(optional)]
+    series_styles: Option<Vec<SeriesStyle>>,
+    #[props(optional)]
+    bands: Option<Vec<Band>>,
+    #[props(optional)]
+    thresholds: Option<Vec<Threshold>>,
+    #[props(optional)]
+    error_bars: Option<Vec<ErrorBars>>,
+    #[props(optional)]
+    annotations: Option<Vec<Annotation>>,
+    #[props(optional)]
+    callouts: Option<Vec<Callout>>,
+    #[props(optional)]
     label_interpolation: Option<fn(f32) -> String>,
+    #[props(optional)]
+    max_label_count: Option<usize>,
+
+    #[props(default = Downsample::Lttb)]
+    downsample: Downsample,
+    #[props(optional)]
+    max_points: Option<usize>,
 
     #[props(optional)]
     lowest: Option<f32>,
@@ -59,6 +210,8 @@ pub struct LineChartProps {
     highest: Option<f32>,
     #[props(default = 8)]
     max_ticks: i32,
+    #[props(optional)]
+    domain_values: Option<Vec<f32>>,
 
     #[props(default = "dx-chart-line".to_string(), into)]
     class_chart_line: String,
@@ -68,6 +221,14 @@ pub struct LineChartProps {
     class_line_path: String,
     #[props(default = "dx-line-dot".to_string(), into)]
     class_line_dot: String,
+    #[props(default = "dx-line-band".to_string(), into)]
+    class_line_band: String,
+    #[props(default = "dx-error-bar".to_string(), into)]
+    class_error_bar: String,
+    #[props(default = "dx-annotation".to_string(), into)]
+    class_annotation: String,
+    #[props(default = "dx-callout".to_string(), into)]
+    class_callout: String,
     #[props(default = "dx-line-label".to_string(), into)]
     class_line_label: String,
     #[props(default = "dx-grid".to_string(), into)]
@@ -78,6 +239,310 @@ pub struct LineChartProps {
     class_grid_label: String,
     #[props(default = "dx-grid-labels".to_string(), into)]
     class_grid_labels: String,
+
+    #[props(optional)]
+    onerror: Option<EventHandler<ChartError>>,
+    #[props(optional)]
+    fallback: Option<Element>,
+}
+
+impl LineChartProps {
+    // A length prop in view box units, falling back to the `default` one when it can't be parsed
+    fn length(&self, length: &str, default: &str) -> f32 {
+        let (width, height) = (self.viewbox_width, self.viewbox_height);
+
+        view_length(
+            length,
+            view_length(default, 0.0, width, height),
+            width,
+            height,
+        )
+    }
+
+    fn geometry(&self) -> LineGeometry {
+        LineGeometry {
+            series: self.series,
+            labels: self.labels.clone(),
+            viewbox_width: self.viewbox_width,
+            viewbox_height: self.viewbox_height,
+            padding_top: self.padding_top,
+            padding_bottom: self.padding_bottom,
+            padding_left: self.padding_left,
+            padding_right: self.padding_right,
+            show_grid_ticks: self.show_grid_ticks,
+            bands: self.bands.clone(),
+            error_bars: self.error_bars.clone(),
+            annotations: self.annotations.clone(),
+            label_interpolation: self.label_interpolation,
+            downsample: self.downsample,
+            max_points: self.max_points,
+            lowest: self.lowest,
+            highest: self.highest,
+            max_ticks: self.max_ticks,
+            domain_values: self.domain_values.clone(),
+        }
+    }
+
+    fn annotations(&self, grid: &Grid, last_slot: f32) -> Vec<ViewAnnotation> {
+        self.annotations
+            .iter()
+            .flatten()
+            .map(|annotation| {
+                annotation.to_view(
+                    grid.x.view(),
+                    |value| grid.ratio_to_view(0.0, value).y,
+                    |position| grid.ratio_to_view(position / last_slot, 0.0).x,
+                    false,
+                )
+            })
+            .collect()
+    }
+
+    // The last point of every series with a label and the height of its label, spread vertically
+    // so the labels of converging lines don't overlap and stay inside the view box
+    fn end_labels(&self, series_points: &[Vec<Option<SeriesPoint>>]) -> Vec<Option<(Point, f32)>> {
+        let labels = match &self.series_labels {
+            Some(labels) if self.show_line_labels => labels.as_slice(),
+            _ => &[],
+        };
+        let ends = series_points
+            .iter()
+            .enumerate()
+            .map(|(i, points)| {
+                labels
+                    .get(i)
+                    .filter(|label| !label.is_empty())
+                    .and_then(|_| points.iter().rev().find_map(|point| point.map(|p| p.view)))
+            })
+            .collect::<Vec<Option<Point>>>();
+
+        let heights = ends.iter().flatten().map(|end| end.y).collect::<Vec<f32>>();
+        let mut relaxed = relax_positions(
+            &heights,
+            TEXT_HEIGHT,
+            TEXT_HEIGHT / 2.0,
+            self.viewbox_height as f32 - TEXT_HEIGHT / 2.0,
+        )
+        .into_iter();
+
+        ends.into_iter()
+            .map(|end| end.zip(relaxed.next()))
+            .collect()
+    }
+
+    // The callouts of the values that exist and aren't gaps
+    fn callouts(&self, grid: &Grid, last_slot: f32) -> Vec<ViewCallout> {
+        let series = self.series.read();
+
+        self.callouts
+            .iter()
+            .flatten()
+            .filter_map(|callout| {
+                let value = *series.get(callout.series())?.get(callout.index())?;
+                let ratio = callout.index() as f32 / last_slot;
+
+                value
+                    .is_finite()
+                    .then(|| callout.to_view(grid.ratio_to_view(ratio, value), grid.x.view()))
+            })
+            .collect()
+    }
+}
+
+// The props of a `LineChart` the grid and the view points depend on, so their memos aren't
+// rebuilt when only the styles, the classes or the fallback element change
+#[allow(unpredictable_function_pointer_comparisons)]
+#[derive(Clone, PartialEq)]
+struct LineGeometry {
+    series: ReadSignal<Series>,
+    labels: Option<Labels>,
+    viewbox_width: i32,
+    viewbox_height: i32,
+    padding_top: i32,
+    padding_bottom: i32,
+    padding_left: i32,
+    padding_right: i32,
+    show_grid_ticks: bool,
+    bands: Option<Vec<Band>>,
+    error_bars: Option<Vec<ErrorBars>>,
+    annotations: Option<Vec<Annotation>>,
+    label_interpolation: Option<fn(f32) -> String>,
+    downsample: Downsample,
+    max_points: Option<usize>,
+    lowest: Option<f32>,
+    highest: Option<f32>,
+    max_ticks: i32,
+    domain_values: Option<Vec<f32>>,
+}
+
+impl LineGeometry {
+    fn validate(&self, series: &Series) -> Result<(), ChartError> {
+        validate_viewbox(self.viewbox_width, self.viewbox_height)?;
+        validate_padding(
+            self.viewbox_width,
+            self.viewbox_height,
+            self.padding_top,
+            self.padding_bottom,
+            self.padding_left,
+            self.padding_right,
+        )?;
+        validate_range(self.lowest, self.highest)?;
+
+        // Series with only gaps still get an empty grid when its range is set
+        match validate_series(series) {
+            Err(ChartError::NoValues) if self.lowest.is_some() && self.highest.is_some() => Ok(()),
+            result => result,
+        }
+    }
+
+    fn grid(&self) -> Result<Grid, ChartError> {
+        let series = self.series.read();
+        self.validate(&series)?;
+
+        let view = Rect::new(
+            self.padding_left as f32,
+            self.padding_top as f32,
+            (self.viewbox_width - self.padding_right) as f32,
+            (self.viewbox_height - self.padding_bottom) as f32,
+        );
+
+        let max_ticks = self.max_ticks.max(3);
+
+        // The bands, error bars and annotations must fit in the value axis along with the series
+        let error_values = ErrorBars::domain_values(self.error_bars.as_ref(), &series);
+        let annotation_values = Annotation::domain_values(self.annotations.as_ref());
+        let domain_values = self
+            .domain_values
+            .iter()
+            .flatten()
+            .chain(
+                self.bands
+                    .iter()
+                    .flatten()
+                    .flat_map(|band| band.lower.iter().chain(&band.upper)),
+            )
+            .chain(&error_values)
+            .chain(&annotation_values)
+            .copied()
+            .collect::<Vec<f32>>();
+
+        let axis_x = Axis::builder()
+            .with_view(view)
+            .with_grid_ticks(self.show_grid_ticks)
+            .with_category_count(series.iter().map(Vec::len).max().unwrap_or(0))
+            .with_labels(self.labels.as_ref());
+
+        let axis_y = Axis::builder()
+            .with_view(view)
+            .with_max_ticks(max_ticks)
+            .with_grid_ticks(self.show_grid_ticks)
+            .with_series(&series)
+            .with_domain_values(&domain_values)
+            .with_label_interpolation(self.label_interpolation)
+            .with_highest(self.highest)
+            .with_lowest(self.lowest);
+
+        Ok(Grid::new(axis_x, axis_y))
+    }
+
+    // The view point of every drawn series value, with `None` for gaps. Points are spread evenly
+    // over the labels axis, or over the whole width when there are more points than labels, and
+    // long series are downsampled to about one point per view unit of the plot width.
+    fn series_points(&self, grid: &Grid) -> Vec<Vec<Option<SeriesPoint>>> {
+        let series = self.series.read();
+        let (last_slot, max_points) = self.sampling(&series);
+
+        series
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                self.downsample
+                    .apply(a, max_points)
+                    .into_iter()
+                    .map(|sample| {
+                        sample.map(|(index, value)| {
+                            let ratio = index as f32 / last_slot;
+                            let error = ErrorBars::get(self.error_bars.as_ref(), i, index).map(
+                                |(minus, plus)| {
+                                    (
+                                        grid.ratio_to_view(ratio, value - minus),
+                                        grid.ratio_to_view(ratio, value + plus),
+                                    )
+                                },
+                            );
+
+                            SeriesPoint {
+                                index,
+                                value,
+                                view: grid.ratio_to_view(ratio, value),
+                                error,
+                            }
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // The path of the band of every series, if it has one, sampled like the series values
+    fn band_paths(&self, grid: &Grid) -> Vec<Option<String>> {
+        let series = self.series.read();
+        let (last_slot, max_points) = self.sampling(&series);
+        let to_view = |(index, value): (usize, f32)| {
+            grid.ratio_to_view(index as f32 / last_slot, value)
+                .to_string()
+        };
+
+        let mut paths = vec![None; series.len()];
+
+        for band in self.bands.iter().flatten() {
+            let Some(path) = paths.get_mut(band.series) else {
+                continue;
+            };
+
+            // Both bounds have the same gaps, so they are split in the same segments, each drawn
+            // as a polygon along the upper bound and back along the lower one
+            let upper = self
+                .downsample
+                .apply(&Band::bound(&band.upper, &band.lower), max_points);
+            let lower = self
+                .downsample
+                .apply(&Band::bound(&band.lower, &band.upper), max_points);
+
+            let commands = upper
+                .split(Option::is_none)
+                .zip(lower.split(Option::is_none))
+                .filter(|(upper, lower)| !upper.is_empty() && !lower.is_empty())
+                .map(|(upper, lower)| {
+                    let points = upper
+                        .iter()
+                        .flatten()
+                        .chain(lower.iter().rev().flatten())
+                        .map(|point| to_view(*point))
+                        .collect::<Vec<String>>();
+
+                    format!("M{}Z", points.join(" L"))
+                })
+                .collect::<Vec<String>>();
+
+            *path = Some(commands.join(" "));
+        }
+
+        paths
+    }
+
+    // The position of the last slot on the labels axis and the maximum number of points drawn
+    // per series. Points are spread evenly over the labels, or over the whole width when there
+    // are more points than labels.
+    fn sampling(&self, series: &Series) -> (f32, usize) {
+        let longest = series.iter().map(Vec::len).max().unwrap_or(0);
+        let slots = self.labels.as_ref().map_or(0, Vec::len).max(longest);
+        let max_points = self.max_points.unwrap_or(
+            (self.viewbox_width - self.padding_left - self.padding_right).max(0) as usize,
+        );
+
+        ((slots as f32 - 1.0).max(1.0), max_points)
+    }
 }
 
 /// This is the `LineChart` function used to render the line chart `Element`.
@@ -114,14 +579,17 @@ pub struct LineChartProps {
 /// # Props
 ///
 /// - `series`: [Vec]<[Vec]<[f32]>> (**required**): The series vector of vectors with the series values.
+///   It also accepts a `Signal`, `Memo` or `ReadSignal` of it, so live data only rebuilds the
+///   chart geometry when the values change.
+///   Missing values can be set as `f32::NAN` to break the line into separate segments.
 /// - `labels`: [Vec]<[String]> (optional): Optional labels to show on the labels axis.
 /// - `series_labels`: [Vec]<[String]> (optional): Optional labels to show for each generated line.
 /// ---
 /// - `width`: &[str] (default: `"100%"`): The SVG element width attribute. It also accepts any
-/// other CSS style, i.e., "200px"
+///   other CSS style, i.e., "200px"
 /// - `height`: &[str] (default: `"100%"`): The SVG height counter-part of the `width` prop above.
 /// - `viewbox_width`: [i32] (default: `600`): The SVG viewbox width. Together with
-/// `viewbox_height` it is useful for adjusting the aspect ratio for longer charts.
+///   `viewbox_height` it is useful for adjusting the aspect ratio for longer charts.
 /// - `viewbox_height`: [i32] (default: `400`): The SVG viewbox height.
 /// ---
 /// - `padding_top`: [i32] (default: `0`): Padding for the top side of the view box.
@@ -130,89 +598,146 @@ pub struct LineChartProps {
 /// - `padding_right`: [i32] (default: `0`): Padding for the right side of the view box.
 /// ---
 /// - `lowest`: [f32] (optional): The lowest number on the chart for the value axis.
-/// - `highest`: [f32] (optional): The highest number on the chart for the value axis.
+/// - `highest`: [f32] (optional): The highest number on the chart for the value axis. With both
+///   `lowest` and `highest` set, series with only gaps draw an empty grid instead of an error.
 /// - `max_ticks`: [i32] (default: `8`): The maximum number of ticks on the generated value axis.
+/// - `domain_values`: [Vec]<[f32]> (optional): Extra values that the generated value axis must
+///   include besides the series ones, e.g. `vec![0.0]` to always show the zero line.
 /// ---
 /// - `show_grid`: [bool] (default: `true`): Show/hide the chart grid.
 /// - `show_dotted_grid`: [bool] (default: `true`): Show the chart grid with dotted style or not.
 /// - `show_grid_ticks`: [bool] (default: `false`): Show the chart grid ticks instead of drawing the
-/// whole grid lines for a cleaner look.
+///   whole grid lines for a cleaner look.
 /// - `show_labels`: [bool] (default: `true`): Show/hide the labels.
 /// - `show_dots`: [bool] (default: `true`): Show/hide the line dots.
+/// - `interactive_dots`: [bool] (default: `false`): Draw every dot as its own element with a
+///   hover title showing its value, instead of all the dots of a line in a single path.
 /// - `show_lines`: [bool] (default: `true`): Show/hide the series lines.
-/// - `show_line_labels`: [bool] (default: `true`): Show/hide the labels for the lines.
+/// - `show_line_labels`: [bool] (default: `true`): Show/hide the labels for the lines. They are
+///   spread vertically when they would overlap, with a leader line to the end of their line.
 /// ---
 /// - `line_width`: &[str] (default: `"1%"`): The width of the series lines.
-/// - `dot_size`: &[str] (default: `"3%"`): The size of the line dots.
+/// - `dot_size`: &[str] (default: `"3%"`): The size of the line dots, in view box units or in
+///   percentage of the view box like the `stroke-width` attribute.
+/// - `series_styles`: [Vec]<[`SeriesStyle`]> (optional): The style of each line, overriding the
+///   props above and the default colors. Lines without one are drawn with the default style.
+/// - `thresholds`: [Vec]<[`Threshold`]> (optional): Rules coloring the line segments by value. The
+///   lines are split where they cross a threshold.
+/// - `bands`: [Vec]<[`Band`]> (optional): Shaded bands between a lower and an upper bound drawn
+///   beneath the lines, i.e. for confidence intervals. The bounds are included in the value axis.
+/// - `error_bars`: [Vec]<[`ErrorBars`]> (optional): Error bars drawn around the line values,
+///   which are included in the value axis.
+/// - `annotations`: [Vec]<[`Annotation`]> (optional): Reference lines, shaded ranges and texts
+///   drawn over the chart. Positions on the labels axis are the indices of the series values.
+/// - `callouts`: [Vec]<[`Callout`]> (optional): Text boxes pointing to single series values with a
+///   leader line.
 /// - `label_interpolation`: fn([f32]) -> [String] (optional): Function for formatting the
-/// generated labels.
+///   generated labels.
+/// - `max_label_count`: [usize] (optional): The maximum number of category labels shown. Labels
+///   that would overlap are also rotated by 45° or 90°, or thinned out to every nth one, based on
+///   their estimated text width.
+/// ---
+/// - `downsample`: [`Downsample`] (default: [`Downsample::Lttb`]): The downsampling of series with
+///   more points than `max_points`, so very long series render a bounded number of vertices.
+/// - `max_points`: [usize] (optional): The maximum number of points drawn per series. Defaults to
+///   the width of the plot area in viewbox units.
 /// ---
 /// - `class_chart_line`: &[str] (default: `"dx-chart-line"`): The HTML element `class` of the
-/// chart.
+///   chart.
 /// - `class_line`: &[str] (default: `"dx-line"`): The HTML element `class` of the whole line.
-/// - `class_line_path`: &[str] (default: `"dx-line"`): The HTML element `class` of the line path.
+/// - `class_line_path`: &[str] (default: `"dx-line"`): The HTML element `class` of the line path,
+///   with the extra `-fill` class for the area under the lines with a fill, gradient or pattern.
 /// - `class_line_dot`: &[str] (default: `"dx-line-dot"`): The HTML element `class` of the line dot.
+/// - `class_line_band`: &[str] (default: `"dx-line-band"`): The HTML element `class` of the line
+///   band.
+/// - `class_error_bar`: &[str] (default: `"dx-error-bar"`): The HTML element `class` of the error
+///   bars of a line.
+/// - `class_annotation`: &[str] (default: `"dx-annotation"`): The HTML element `class` of the
+///   annotations group, which is also the prefix of the `-line`, `-range` and `-label` classes of
+///   the annotation elements.
+/// - `class_callout`: &[str] (default: `"dx-callout"`): The HTML element `class` of the callouts
+///   group, which is also the prefix of the `-line`, `-point`, `-box` and `-label` classes of the
+///   callout elements.
 /// - `class_line_label`: &[str] (default: `"dx-line-label"`): The HTML element `class` of the line
-/// labels.
+///   labels, with the extra `-leader` class for the lines to the labels moved away from their line.
 /// - `class_grid`: &[str] (default: `"dx-grid"`): The HTML element `class` of the grid.
 /// - `class_grid_line`: &[str] (default: `"dx-grid-line"`): The HTML element `class` of every grid
-/// line.
+///   line.
 /// - `class_grid_label`: &[str] (default: `"dx-grid-label"`): The HTML element `class` of the grid
-/// labels.
+///   labels.
 /// - `class_grid_labels`: &[str] (default: `"dx-grid-labels"`): The HTML element `class` of the
-/// group of grid labels.
+///   group of grid labels.
+/// ---
+/// - `onerror`: [EventHandler]<[`ChartError`]> (optional): Called with the error when the props
+///   fail validation and the chart can't be drawn.
+/// - `fallback`: [Element] (optional): The element rendered instead of the chart on errors. A short
+///   error message is shown by default.
 #[allow(non_snake_case)]
 pub fn LineChart(props: LineChartProps) -> Element {
-    for series in props.series.iter() {
-        if series.is_empty() {
-            return rsx!("Line chart error: empty series");
-        }
-    }
+    // The grid and the view points only depend on the geometry props and the series values. The
+    // geometry is compared with the last one on every render, so they're rebuilt when it or the
+    // series signal changes, but not when only the styles, the classes or the fallback element do
+    let geometry = props.geometry();
+    let grid = use_memo(use_reactive((&geometry,), |(geometry,)| geometry.grid()));
+    let series_points = use_memo(use_reactive(
+        (&geometry,),
+        move |(geometry,)| match &*grid.read() {
+            Ok(grid) => geometry.series_points(grid),
+            Err(_) => Vec::new(),
+        },
+    ));
+    let band_paths = use_memo(use_reactive(
+        (&geometry,),
+        move |(geometry,)| match &*grid.read() {
+            Ok(grid) => geometry.band_paths(grid),
+            Err(_) => Vec::new(),
+        },
+    ));
+
+    let defs_prefix = use_defs_prefix();
+
+    let grid = match use_validation(grid(), props.onerror) {
+        Ok(grid) => grid,
+        Err(error) => return error_element("Line chart", &error, props.fallback),
+    };
 
-    let view = Rect::new(
-        props.padding_left as f32,
-        props.padding_top as f32,
-        (props.viewbox_width - props.padding_right) as f32,
-        (props.viewbox_height - props.padding_bottom) as f32,
-    );
-
-    let max_ticks = props.max_ticks.max(3);
-
-    let axis_x = Axis::builder()
-        .with_view(view)
-        .with_grid_ticks(props.show_grid_ticks)
-        .with_labels(props.labels.as_ref());
-
-    let axis_y = Axis::builder()
-        .with_view(view)
-        .with_max_ticks(max_ticks)
-        .with_grid_ticks(props.show_grid_ticks)
-        .with_series(&props.series)
-        .with_label_interpolation(props.label_interpolation)
-        .with_highest(props.highest)
-        .with_lowest(props.lowest);
-
-    let grid = Grid::new(axis_x, axis_y);
     let lines = grid.lines();
     let generated_labels = grid.y.generated_labels();
+    let (last_slot, _) = geometry.sampling(&props.series.read());
+    let annotations = props.annotations(&grid, last_slot);
+    let callouts = props.callouts(&grid, last_slot);
 
+    // The labels with their rotation transform, for the category ones that would overlap
     let grid_labels = if props.show_labels {
+        let mut grid_labels = Vec::<(TextData, &String, Option<String>)>::new();
+
         if let Some(labels) = props.labels.as_ref() {
-            Some(
-                grid.text_data(Some(labels.len()), Some(generated_labels.len()))
-                    .into_iter()
-                    .zip(labels.iter().chain(generated_labels.iter()))
-                    .collect::<Vec<(TextData, &String)>>(),
-            )
-        } else {
-            Some(
-                grid.y
-                    .text_data(generated_labels.len())
+            let layout = grid.x.label_layout(labels, props.max_label_count);
+
+            grid_labels.extend(
+                grid.x
+                    .text_data(labels.len())
                     .into_iter()
-                    .zip(generated_labels.iter())
-                    .collect::<Vec<(TextData, &String)>>(),
-            )
+                    .zip(labels.iter())
+                    .enumerate()
+                    .filter(|(i, _)| layout.shows(*i))
+                    .map(|(_, (text, label))| {
+                        let text = layout.text(text);
+                        let transform = layout.transform(&text);
+                        (text, label, transform)
+                    }),
+            );
         }
+
+        grid_labels.extend(
+            grid.y
+                .text_data(generated_labels.len())
+                .into_iter()
+                .zip(generated_labels.iter())
+                .map(|(text, label)| (text, label, None)),
+        );
+
+        Some(grid_labels)
     } else {
         None
     };
@@ -227,8 +752,10 @@ pub fn LineChart(props: LineChartProps) -> Element {
     let string_binding = String::new();
     let vec_binding = vec![];
 
-    let series_rsx = props
-        .series
+    let series_points = series_points.read();
+    let band_paths = band_paths.read();
+    let end_labels = props.end_labels(&series_points);
+    let series_rsx = series_points
         .iter()
         .enumerate()
         .zip(
@@ -239,62 +766,198 @@ pub fn LineChart(props: LineChartProps) -> Element {
                 .iter()
                 .chain(std::iter::repeat(&string_binding)),
         )
-        .map(|((i, a), label)| {
-            let mut commands = Vec::<String>::with_capacity(a.len());
-            let mut dots = Vec::<Rect>::with_capacity(a.len());
-            let mut text_point: Option<Point> = None;
-
+        .map(|((i, points), label)| {
             color_var -= 75.0 * (1.0 / (i + 1) as f32);
 
-            for (index, v) in a.iter().enumerate() {
-                let point = grid.world_to_view(index as f32, *v, false);
+            let style = SeriesStyle::get(props.series_styles.as_ref(), i);
+            let color = style.color(color_var);
+            let area_fill = style.paint(&defs_prefix, i).or(style.fill.clone());
+            let pattern =
+                style.pattern_element(&defs_prefix, i, style.fill.as_deref().unwrap_or(&color));
+            let line_width = style.stroke_width.as_ref().unwrap_or(&props.line_width);
+            let marker = style.marker.as_ref().unwrap_or(&Marker::Circle);
+            let dot_size =
+                props.length(style.marker_size.as_ref().unwrap_or(&props.dot_size), "3%");
+            let marker_stroke_width = marker.stroke_width(dot_size);
+            let line_style = style.line_style.as_ref().unwrap_or(&LineStyle::Solid);
+            let dash_array = line_style.dash_array(props.length(line_width, "1%"), true);
+
+            // The line before `dash_from` is drawn solid in its own path
+            let mut solid_commands = Vec::<String>::new();
+            let mut commands = Vec::<String>::with_capacity(points.len());
+            let mut dot_commands = Vec::<String>::new();
+            let mut error_commands = Vec::<String>::new();
+            let mut dots = Vec::<SeriesPoint>::new();
+
+            // A gap (non-finite value) ends the current path segment so the next value starts a
+            // new one with a move command
+            let mut pen_down = false;
+            let mut solid = false;
+
+            for series_point in points {
+                let Some(series_point) = *series_point else {
+                    pen_down = false;
+                    continue;
+                };
+                let point = series_point.view;
 
-                if index == 0 {
-                    commands.push(format!("M{},{}", point.x, point.y));
+                let before_dash = style
+                    .dash_from
+                    .is_some_and(|index| series_point.index < index);
+                if solid && !before_dash && pen_down {
+                    // Joins the solid part to the first point of the styled one
+                    solid_commands.push(format!("L{},{}", point.x, point.y));
+                    pen_down = false;
+                }
+                solid = before_dash;
+
+                let path_commands = if solid {
+                    &mut solid_commands
+                } else {
+                    &mut commands
+                };
+                if pen_down {
+                    path_commands.push(format!("L{},{}", point.x, point.y));
                 } else {
-                    commands.push(format!("L{},{}", point.x, point.y));
+                    path_commands.push(format!("M{},{}", point.x, point.y));
+                    pen_down = true;
                 }
 
-                if props.show_dots {
-                    dots.push(Rect::new(point.x, point.y, point.x + 0.1, point.y));
+                // The dots of a line are all drawn in a single path unless each needs its own
+                // element for hovering
+                if props.show_dots && *marker != Marker::None {
+                    if props.interactive_dots {
+                        dots.push(series_point);
+                    } else {
+                        dot_commands.push(marker.commands(point, dot_size));
+                    }
                 }
 
-                if !label.is_empty() && index == (a.len() - 1) {
-                    text_point = Some(point);
+                if let Some((low, high)) = series_point.error {
+                    error_commands.push(ErrorBars::commands(low, high, dot_size));
                 }
             }
 
+            let threshold_paths = props
+                .thresholds
+                .as_deref()
+                .map_or(Vec::new(), |thresholds| {
+                    threshold_paths(points, thresholds, style.dash_from)
+                });
+            let solid_commands = solid_commands.join(" ");
             let commands = commands.join(" ");
+            let dot_commands = dot_commands.join(" ");
+            let error_commands = error_commands.join(" ");
 
             rsx! {
                 g {
-                    class: "{props.class_line}-{i}",
-                    path {
-                        d: "{commands}",
-                        class: "{props.class_line_path}",
-                        stroke: "rgb({color_var}, 40, 40)",
-                        stroke_width: "{props.line_width}",
-                        stroke_linecap: "round",
-                        fill: "transparent",
-                    },
+                    class: style.class(format!("{}-{i}", props.class_line)),
+                    opacity: style.opacity,
+                    if let Some(pattern) = pattern {
+                        defs {
+                            {pattern}
+                        }
+                    }
+                    if let Some(Some(band)) = band_paths.get(i) {
+                        path {
+                            d: "{band}",
+                            class: "{props.class_line_band}",
+                            fill: "{color}",
+                            fill_opacity: "0.2",
+                            stroke: "none",
+                        }
+                    }
+                    // The area is closed down to the bottom of the value axis under the line
+                    if let Some(area_fill) = area_fill {
+                        path {
+                            d: area_commands(points, grid.y.view().max.y),
+                            class: "{props.class_line_path}-fill",
+                            stroke: "none",
+                            fill: "{area_fill}",
+                        }
+                    }
+                    if !threshold_paths.is_empty() {
+                        for (solid, threshold_color, commands) in threshold_paths {
+                            path {
+                                d: "{commands}",
+                                class: "{props.class_line_path}",
+                                stroke: threshold_color.unwrap_or(color.clone()),
+                                stroke_width: "{line_width}",
+                                stroke_dasharray: if solid { None } else { dash_array.clone() },
+                                stroke_linecap: "round",
+                                fill: "transparent",
+                            }
+                        }
+                    } else {
+                        if !solid_commands.is_empty() {
+                            path {
+                                d: "{solid_commands}",
+                                class: "{props.class_line_path}",
+                                stroke: "{color}",
+                                stroke_width: "{line_width}",
+                                stroke_linecap: "round",
+                                fill: "transparent",
+                            }
+                        }
+                        path {
+                            d: "{commands}",
+                            class: "{props.class_line_path}",
+                            stroke: "{color}",
+                            stroke_width: "{line_width}",
+                            stroke_dasharray: dash_array,
+                            stroke_linecap: "round",
+                            fill: "transparent",
+                        }
+                    }
+                    if !error_commands.is_empty() {
+                        path {
+                            d: "{error_commands}",
+                            class: "{props.class_error_bar}",
+                            stroke: "{color}",
+                            stroke_width: "0.3%",
+                            fill: "none",
+                        }
+                    }
+                    if !dot_commands.is_empty() {
+                        path {
+                            d: "{dot_commands}",
+                            class: "{props.class_line_dot}",
+                            stroke: "{color}",
+                            stroke_width: "{marker_stroke_width}",
+                            stroke_linecap: marker.linecap(),
+                            fill: "none",
+                        }
+                    }
                     for d in dots {
-                        line {
-                            x1: "{d.min.x}",
-                            y1: "{d.min.y}",
-                            x2: "{d.max.x}",
-                            y2: "{d.max.y}",
+                        path {
+                            d: marker.commands(d.view, dot_size),
                             class: "{props.class_line_dot}",
-                            stroke: "rgb({color_var}, 40, 40)",
-                            stroke_width: "{props.dot_size}",
-                            stroke_linecap: "round",
+                            stroke: "{color}",
+                            stroke_width: "{marker_stroke_width}",
+                            stroke_linecap: marker.linecap(),
+                            fill: "none",
+                            title {
+                                {dot_title(label, &d, props.label_interpolation)}
+                            }
                         }
                     }
-                    for point in text_point {
+                    if let Some(Some((point, y))) = end_labels.get(i) {
+                        // Labels moved away from their line point to it
+                        if (point.y - y).abs() > 1.0 {
+                            path {
+                                d: format_args!("M{},{} L{},{y}", point.x + 3.0, point.y, point.x + 8.0),
+                                class: "{props.class_line_label}-leader",
+                                stroke: "{color}",
+                                stroke_width: "1",
+                                fill: "none",
+                            }
+                        }
                         text {
                             dx: format_args!("{}", point.x + 10.0),
-                            dy: "{point.y}",
+                            dy: "{y}",
                             text_anchor: "start",
-                            color: "rgb({color_var}, 40, 40)",
+                            alignment_baseline: "middle",
+                            color: "{color}",
                             class: "{props.class_line_label}",
                             "{label}"
                         }
@@ -303,6 +966,18 @@ pub fn LineChart(props: LineChartProps) -> Element {
             }
         });
 
+    let gradients = props
+        .series_styles
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(i, style)| {
+            style
+                .gradient
+                .as_ref()
+                .map(|gradient| gradient.element(format!("{defs_prefix}-gradient-{i}"), None))
+        });
+
     rsx! {
         div {
             svg {
@@ -312,6 +987,11 @@ pub fn LineChart(props: LineChartProps) -> Element {
                 class: "{props.class_chart_line}",
                 preserve_aspect_ratio: "xMidYMid meet",
                 view_box: "0 0 {props.viewbox_width} {props.viewbox_height}",
+                defs {
+                    for gradient in gradients {
+                        {gradient}
+                    }
+                }
                 if props.show_grid {
                     g {
                         class: "{props.class_grid}",
@@ -332,21 +1012,280 @@ pub fn LineChart(props: LineChartProps) -> Element {
                 for labels in grid_labels {
                     g {
                         class: "{props.class_grid_labels}",
-                        for (text, label) in labels {
+                        for (text, label, transform) in labels {
                             text {
                                 dx: "{text.x}",
                                 dy: "{text.y}",
                                 text_anchor: "{text.anchor}",
                                 class: "{props.class_grid_label}",
                                 alignment_baseline: "{text.baseline}",
+                                transform,
                                 "{label}"
                             }
                         }
                     }
                 }
 
+                if !annotations.is_empty() {
+                    {annotations_element(&annotations, &props.class_annotation, true)}
+                }
+
                 {series_rsx}
+
+                if !annotations.is_empty() {
+                    {annotations_element(&annotations, &props.class_annotation, false)}
+                }
+
+                if !callouts.is_empty() {
+                    {callouts_element(&callouts, &props.class_callout)}
+                }
             }
         }
     }
 }
+
+// The area between the line and the `baseline`, closed in a polygon per segment between the gaps
+fn area_commands(points: &[Option<SeriesPoint>], baseline: f32) -> String {
+    points
+        .split(Option::is_none)
+        .filter_map(|segment| {
+            let first = segment.first().copied().flatten()?.view;
+            let last = segment.last().copied().flatten()?.view;
+            let line = segment
+                .iter()
+                .flatten()
+                .map(|point| point.view.to_string())
+                .collect::<Vec<String>>();
+
+            Some(format!(
+                "M{},{baseline} L{} L{},{baseline}Z",
+                first.x,
+                line.join(" L"),
+                last.x
+            ))
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// The segments between consecutive values, split where they cross a threshold and grouped in a
+// path per threshold color and per solid or styled part of the line (see `dash_from`), with
+// `None` for the series color
+fn threshold_paths(
+    points: &[Option<SeriesPoint>],
+    thresholds: &[Threshold],
+    dash_from: Option<usize>,
+) -> Vec<(bool, Option<String>, String)> {
+    let mut paths = BTreeMap::<(bool, Option<String>), Vec<String>>::new();
+
+    for pair in points.windows(2) {
+        let [Some(a), Some(b)] = pair else {
+            continue;
+        };
+
+        let solid = dash_from.is_some_and(|index| a.index < index);
+        let delta = b.value - a.value;
+
+        // The fractions of the segment where it crosses the thresholds, in order
+        let mut cuts = thresholds
+            .iter()
+            .map(|threshold| (threshold.value() - a.value) / delta)
+            .filter(|t| *t > 0.0 && *t < 1.0)
+            .collect::<Vec<f32>>();
+        cuts.sort_by(f32::total_cmp);
+        cuts.push(1.0);
+
+        let at = |t: f32| {
+            Point::new(
+                a.view.x + (b.view.x - a.view.x) * t,
+                a.view.y + (b.view.y - a.view.y) * t,
+            )
+        };
+
+        let mut start = 0.0;
+        for end in cuts {
+            let value = a.value + delta * (start + end) / 2.0;
+            let color = Threshold::color(thresholds, value).map(str::to_string);
+
+            paths
+                .entry((solid, color))
+                .or_default()
+                .push(format!("M{}L{}", at(start), at(end)));
+            start = end;
+        }
+    }
+
+    paths
+        .into_iter()
+        .map(|((solid, color), commands)| (solid, color, commands.join(" ")))
+        .collect()
+}
+
+// The hover text of an interactive dot
+fn dot_title(label: &str, point: &SeriesPoint, interpolation: Option<fn(f32) -> String>) -> String {
+    let value = if let Some(func) = interpolation {
+        func(point.value)
+    } else {
+        format!("{}", point.value)
+    };
+
+    if label.is_empty() {
+        value
+    } else {
+        format!("{label}: {value}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(len: usize, gaps: &[usize]) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                if gaps.contains(&i) {
+                    f32::NAN
+                } else {
+                    (i as f32 / 20.0).sin()
+                }
+            })
+            .collect()
+    }
+
+    fn point(index: usize, value: f32) -> Option<SeriesPoint> {
+        Some(SeriesPoint {
+            index,
+            value,
+            view: Point::new(index as f32 * 10.0, 100.0 - value * 10.0),
+            error: None,
+        })
+    }
+
+    #[test]
+    fn area_is_closed_down_to_the_baseline_per_segment() {
+        let points = [point(0, 1.0), point(1, 3.0), None, point(3, 2.0), None];
+
+        assert_eq!(
+            area_commands(&points, 100.0),
+            "M0,100 L0,90 L10,70 L10,100Z M30,100 L30,80 L30,100Z"
+        );
+        assert_eq!(area_commands(&[None, None], 100.0), "");
+    }
+
+    #[test]
+    fn threshold_paths_split_segments_at_the_thresholds() {
+        let points = [point(0, 0.0), point(1, 10.0), point(2, 0.0)];
+        let thresholds = [
+            Threshold::above(2.5, "orange"),
+            Threshold::above(7.5, "red"),
+        ];
+        let path = |solid, color: Option<&str>, commands: &str| {
+            (solid, color.map(str::to_string), commands.to_string())
+        };
+
+        assert_eq!(
+            threshold_paths(&points, &thresholds, Some(1)),
+            [
+                path(false, None, "M17.5,75L20,100"),
+                path(false, Some("orange"), "M12.5,25L17.5,75"),
+                path(false, Some("red"), "M10,0L12.5,25"),
+                path(true, None, "M0,100L2.5,75"),
+                path(true, Some("orange"), "M2.5,75L7.5,25"),
+                path(true, Some("red"), "M7.5,25L10,0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn threshold_paths_keep_flat_segments_whole() {
+        let points = [point(0, 5.0), point(1, 5.0)];
+        let thresholds = [
+            Threshold::above(2.5, "orange"),
+            Threshold::above(5.0, "red"),
+        ];
+
+        assert_eq!(
+            threshold_paths(&points, &thresholds, None),
+            [(false, Some("orange".to_string()), "M0,50L10,50".to_string())]
+        );
+    }
+
+    #[test]
+    fn downsample_keeps_the_gaps_and_segment_ends() {
+        let values = values(1000, &[300, 700, 701]);
+
+        for downsample in [Downsample::Lttb, Downsample::MinMax] {
+            let sampled = downsample.apply(&values, 100);
+            let points = sampled.iter().flatten().collect::<Vec<_>>();
+            assert!(points.len() <= 100, "len: {}", points.len());
+            assert!(points.windows(2).all(|w| w[0].0 < w[1].0));
+
+            let segments = sampled
+                .split(Option::is_none)
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| {
+                    let first = segment.first().copied().flatten().map(|p| p.0);
+                    let last = segment.last().copied().flatten().map(|p| p.0);
+                    (first, last)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                segments,
+                [
+                    (Some(0), Some(299)),
+                    (Some(301), Some(699)),
+                    (Some(702), Some(999))
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn downsample_keeps_the_ends_of_small_segments() {
+        let values = values(1000, &[2, 4]);
+        let sampled = Downsample::Lttb.apply(&values, 50);
+
+        assert_eq!(
+            sampled[..5],
+            [
+                Some((0, values[0])),
+                Some((1, values[1])),
+                None,
+                Some((3, values[3])),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn downsample_bounds_series_with_many_gaps() {
+        let values = values(100_000, &[])
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| if i % 3 == 2 { f32::NAN } else { v })
+            .collect::<Vec<_>>();
+
+        for downsample in [Downsample::Lttb, Downsample::MinMax] {
+            let sampled = downsample.apply(&values, 600);
+            let points = sampled.iter().flatten().collect::<Vec<_>>();
+            assert!(points.len() <= 600, "len: {}", points.len());
+            assert!(points.len() >= 500, "len: {}", points.len());
+            assert!(points.windows(2).all(|w| w[0].0 < w[1].0));
+            assert!(points.iter().all(|p| p.0 % 3 != 2));
+        }
+    }
+
+    #[test]
+    fn downsample_passes_small_inputs_through() {
+        let values = values(100, &[10]);
+        let all = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| v.is_finite().then_some((i, *v)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(Downsample::Lttb.apply(&values, 99), all);
+        assert_eq!(Downsample::MinMax.apply(&values, 200), all);
+        assert_eq!(Downsample::None.apply(&values, 10), all);
+    }
+}
@@ -1,23 +1,44 @@
 use dioxus::prelude::*;
 
-use crate::types::{Labels, Point};
-use crate::utils::{normalize_series, polar_to_cartesian};
+use crate::charts::style::{use_defs_prefix, Gradient, Pattern};
+use crate::error::*;
+use crate::types::{Labels, Point, Rect};
+use crate::utils::{normalize_series, polar_to_cartesian, relax_positions, TEXT_HEIGHT};
 
 /// A hint for the automatic positioning of labels in the pie chart.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum LabelPosition {
     /// To position the label inside the pie chart.
     Inside,
-    /// To position the label outside close to the border of the pie chart.
+    /// To position the label outside close to the border of the pie chart, connected to its
+    /// slice with a leader line and spread vertically to avoid overlapping the other labels.
     Outside,
     /// To position the label in the center for manually positioning with the `label_offset` prop.
     Center,
 }
 
+/// The content of the labels of the pie chart, computed from the series values and `labels`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LabelMode {
+    /// The value, formatted with the `label_interpolation` prop, i.e. "59.54".
+    Value,
+    /// The percentage of the value in the total, i.e. "59.5%".
+    Percentage,
+    /// The label name.
+    Name,
+    /// The label name and the value, i.e. "Asia 59.54".
+    NameValue,
+    /// The label name and the percentage, i.e. "Asia 59.5%".
+    NamePercentage,
+    /// The value and the percentage, i.e. "59.54 (59.5%)".
+    ValuePercentage,
+}
+
 /// The `PieChart` properties struct for the configuration of the pie chart.
+#[allow(unpredictable_function_pointer_comparisons)]
 #[derive(Clone, PartialEq, Props)]
 pub struct PieChartProps {
-    series: Vec<f32>,
+    series: ReadSignal<Vec<f32>>,
     #[props(optional)]
     labels: Option<Labels>,
 
@@ -38,6 +59,8 @@ pub struct PieChartProps {
     label_offset: f32,
     #[props(optional)]
     label_interpolation: Option<fn(f32) -> String>,
+    #[props(optional)]
+    label_mode: Option<LabelMode>,
 
     #[props(default)]
     start_angle: f32,
@@ -48,11 +71,23 @@ pub struct PieChartProps {
     #[props(default)]
     padding: f32,
 
+    #[props(optional)]
+    min_slice_ratio: Option<f32>,
+    #[props(optional)]
+    max_slices: Option<usize>,
+    #[props(default = "Other".to_string(), into)]
+    other_label: String,
+
     #[props(default = false)]
     donut: bool,
     #[props(default = 40.0)]
     donut_width: f32,
 
+    #[props(optional)]
+    gradients: Option<Vec<Gradient>>,
+    #[props(optional)]
+    patterns: Option<Vec<Pattern>>,
+
     #[props(default = "dx-pie-chart".to_string(), into)]
     class_chart: String,
     #[props(default = "dx-series".to_string(), into)]
@@ -61,6 +96,321 @@ pub struct PieChartProps {
     class_slice: String,
     #[props(default = "dx-label".to_string(), into)]
     class_label: String,
+
+    #[props(optional)]
+    onerror: Option<EventHandler<ChartError>>,
+    #[props(optional)]
+    fallback: Option<Element>,
+}
+
+impl PieChartProps {
+    fn geometry(&self) -> PieGeometry {
+        PieGeometry {
+            series: self.series,
+            viewbox_width: self.viewbox_width,
+            viewbox_height: self.viewbox_height,
+            label_position: self.label_position,
+            label_offset: self.label_offset,
+            start_angle: self.start_angle,
+            total: self.total,
+            show_ratio: self.show_ratio,
+            padding: self.padding,
+            min_slice_ratio: self.min_slice_ratio,
+            max_slices: self.max_slices,
+            donut: self.donut,
+            donut_width: self.donut_width,
+        }
+    }
+
+    // The name of the value at `index`, from the `labels` or the `other_label` for the "Other"
+    // slice at the `other` index
+    fn name(&self, index: usize, other: Option<usize>) -> Option<&str> {
+        if other == Some(index) {
+            Some(&self.other_label)
+        } else {
+            self.labels
+                .as_ref()
+                .and_then(|labels| labels.get(index))
+                .map(String::as_str)
+        }
+    }
+
+    fn format_value(&self, value: f32) -> String {
+        if let Some(func) = self.label_interpolation {
+            func(value)
+        } else {
+            value.to_string()
+        }
+    }
+
+    // The text of the label of every value, empty for the labels that are not shown, with the
+    // "Other" slice at the `other` index
+    fn label_texts(&self, series: &[f32], other: Option<usize>) -> Vec<String> {
+        let Some(mode) = self.label_mode.filter(|_| self.show_labels) else {
+            // The labels replace the values, which are only shown with `show_labels`
+            return if self.labels.is_some() {
+                (0..series.len())
+                    .map(|i| self.name(i, other).unwrap_or_default().to_string())
+                    .collect()
+            } else if self.show_labels {
+                series
+                    .iter()
+                    .map(|value| self.format_value(*value))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+        };
+
+        let total = self
+            .total
+            .unwrap_or_else(|| series.iter().filter(|v| v.is_finite()).sum());
+        series
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let name = self.name(i, other).unwrap_or_default();
+                let value_text = self.format_value(*value);
+                let percentage = if total > 0.0 {
+                    format!("{:.1}%", value / total * 100.0)
+                } else {
+                    String::new()
+                };
+
+                let text = match mode {
+                    LabelMode::Value => value_text,
+                    LabelMode::Percentage => percentage,
+                    LabelMode::Name => name.to_string(),
+                    LabelMode::NameValue => format!("{name} {value_text}"),
+                    LabelMode::NamePercentage => format!("{name} {percentage}"),
+                    LabelMode::ValuePercentage => format!("{value_text} ({percentage})"),
+                };
+
+                text.trim().to_string()
+            })
+            .collect()
+    }
+}
+
+// The props of a `PieChart` the slices depend on, see `LineGeometry`
+#[derive(Clone, PartialEq)]
+struct PieGeometry {
+    series: ReadSignal<Vec<f32>>,
+    viewbox_width: i32,
+    viewbox_height: i32,
+    label_position: LabelPosition,
+    label_offset: f32,
+    start_angle: f32,
+    total: Option<f32>,
+    show_ratio: Option<f32>,
+    padding: f32,
+    min_slice_ratio: Option<f32>,
+    max_slices: Option<usize>,
+    donut: bool,
+    donut_width: f32,
+}
+
+impl PieGeometry {
+    fn validate(&self, series: &[f32]) -> Result<(), ChartError> {
+        validate_viewbox(self.viewbox_width, self.viewbox_height)?;
+
+        if self.radius() <= 0.0 {
+            return Err(ChartError::InvalidPadding);
+        }
+
+        if series.is_empty() {
+            return Err(ChartError::NoSeries);
+        }
+
+        if let Some(index) = series.iter().position(|v| *v < 0.0) {
+            return Err(ChartError::NegativeValue(index));
+        }
+
+        if series.iter().any(|v| v.is_finite() && *v > 0.0) {
+            Ok(())
+        } else {
+            Err(ChartError::NoValues)
+        }
+    }
+
+    // The series with the grouped values left out and their sum appended for the "Other" slice,
+    // and the indices of the grouped values
+    fn grouped_series(&self, series: &[f32]) -> (Vec<f32>, Vec<usize>) {
+        let other = other_indices(series, self.min_slice_ratio, self.max_slices);
+        let mut grouped = series.to_vec();
+
+        if !other.is_empty() {
+            grouped.push(other.iter().map(|i| series[*i]).sum());
+            for i in &other {
+                grouped[*i] = 0.0;
+            }
+        }
+
+        (grouped, other)
+    }
+
+    // The path and label position of the slice of every value, with `None` for the values that
+    // don't get a slice.
+    fn slices(&self) -> Result<Vec<Option<Slice>>, ChartError> {
+        let series = self.series.read();
+        self.validate(&series)?;
+        let (series, _) = self.grouped_series(&series);
+
+        let center = self.center();
+        let radius = self.radius();
+        let label_radius = match self.label_position {
+            LabelPosition::Inside => radius / 2.0 + self.label_offset,
+            LabelPosition::Outside => radius + self.label_offset,
+            LabelPosition::Center => 0.0 + self.label_offset,
+        };
+
+        let normalized_series = normalize_series(&series);
+        let normalized_sum: f32 = normalized_series.iter().sum();
+
+        let values_total: f32 = if let Some(r) = self.show_ratio {
+            1.0 / r.clamp(0.0001, 1.0) * normalized_sum
+        } else if let Some(v) = self.total {
+            let series_sum: f32 = series.iter().filter(|v| v.is_finite()).sum();
+            (normalized_sum / series_sum * v).max(normalized_sum)
+        } else {
+            normalized_sum
+        };
+
+        let mut m_start_angle = self.start_angle;
+        let mut first = true;
+
+        let mut slices = normalized_series
+            .iter()
+            .map(|v| {
+                if *v == 0.0 {
+                    return None;
+                }
+
+                let mut end_angle = if values_total > 0.0 {
+                    m_start_angle + (v / values_total) * 360.0
+                } else {
+                    0.0
+                };
+                let overlap_start_angle = if first {
+                    m_start_angle
+                } else {
+                    (m_start_angle - 0.4).max(0.0)
+                };
+                if end_angle - overlap_start_angle >= 359.99 {
+                    end_angle = overlap_start_angle + 359.99
+                }
+
+                let start_position = polar_to_cartesian(center, radius, overlap_start_angle);
+                let end_position = polar_to_cartesian(center, radius, end_angle);
+                let large_arc = i32::from(end_angle - m_start_angle > 180.0);
+
+                let path = if self.donut {
+                    let donut_radius = radius - self.donut_width;
+                    let start_inside_position =
+                        polar_to_cartesian(center, donut_radius, overlap_start_angle);
+                    let end_inside_position = polar_to_cartesian(center, donut_radius, end_angle);
+                    let large_arc_inside = large_arc;
+
+                    format!(
+                        "M{end_position}\
+                         A{radius},{radius},0,{large_arc},0,{start_position}\
+                         L{start_inside_position}\
+                         A{donut_radius},{donut_radius},0,{large_arc_inside},1,{end_inside_position}Z"
+                    )
+                } else {
+                    format!(
+                        "M{end_position}\
+                         A{radius},{radius},0,{large_arc},0,{start_position}\
+                         L{center}Z"
+                    )
+                };
+
+                let mid_angle = m_start_angle + (end_angle - m_start_angle) / 2.0;
+                let label_position = polar_to_cartesian(center, label_radius, mid_angle);
+
+                first = false;
+                m_start_angle = end_angle;
+                Some(Slice {
+                    path,
+                    mid_angle,
+                    label_position,
+                    label_anchor: "middle",
+                    leader: None,
+                })
+            })
+            .collect::<Vec<Option<Slice>>>();
+
+        if self.label_position == LabelPosition::Outside {
+            self.outside_labels(&mut slices);
+        }
+
+        Ok(slices)
+    }
+
+    // Places the outside labels at the end of a leader line from the middle of their slice, aligned
+    // away from the pie on each side and spread vertically so small neighbouring slices don't get
+    // overlapping labels
+    fn outside_labels(&self, slices: &mut [Option<Slice>]) {
+        let center = self.center();
+        let radius = self.radius();
+        let elbow_radius = radius + self.label_offset + PIE_LEADER_LENGTH;
+
+        for right in [true, false] {
+            let side = slices
+                .iter_mut()
+                .flatten()
+                .filter(|slice| {
+                    (polar_to_cartesian(center, 1.0, slice.mid_angle).x >= center.x) == right
+                })
+                .collect::<Vec<&mut Slice>>();
+            let heights = side
+                .iter()
+                .map(|slice| polar_to_cartesian(center, elbow_radius, slice.mid_angle).y)
+                .collect::<Vec<f32>>();
+            let relaxed = relax_positions(
+                &heights,
+                TEXT_HEIGHT,
+                TEXT_HEIGHT / 2.0,
+                self.viewbox_height as f32 - TEXT_HEIGHT / 2.0,
+            );
+            let direction = if right { 1.0 } else { -1.0 };
+
+            for (slice, y) in side.into_iter().zip(relaxed) {
+                let edge = polar_to_cartesian(center, radius, slice.mid_angle);
+                let elbow = polar_to_cartesian(center, elbow_radius, slice.mid_angle);
+                let end = Point::new(elbow.x + direction * PIE_LEADER_LENGTH, y);
+
+                slice.leader = Some(format!("{edge} {elbow} {end}"));
+                slice.label_position = Point::new(end.x + direction * 3.0, y);
+                slice.label_anchor = if right { "start" } else { "end" };
+            }
+        }
+    }
+
+    fn center(&self) -> Point {
+        Point::new(
+            self.viewbox_width as f32 / 2.0,
+            self.viewbox_height as f32 / 2.0,
+        )
+    }
+
+    fn radius(&self) -> f32 {
+        let center = self.center();
+        center.x.min(center.y) - 30.0 - self.padding
+    }
+}
+
+// The length of both segments of the leader lines of outside labels, in view units
+const PIE_LEADER_LENGTH: f32 = 12.0;
+
+#[derive(Clone, PartialEq)]
+struct Slice {
+    path: String,
+    mid_angle: f32,
+    label_position: Point,
+    label_anchor: &'static str,
+    // The points of the polyline from the slice to its outside label
+    leader: Option<String>,
 }
 
 /// This is the `PieChart` function used to render the pie chart `Element`.
@@ -78,7 +428,6 @@ pub struct PieChartProps {
 ///         PieChart {
 ///             start_angle: -60.0,
 ///             label_position: LabelPosition::Outside,
-///             label_offset: 35.0,
 ///             padding: 20.0,
 ///             series: vec![59.54, 17.2, 9.59, 7.6, 5.53, 0.55]
 ///             labels: vec!["Asia".into(), "Africa".into(), "Europe".into(), "N. America".into(), "S. America".into(), "Oceania".into()],
@@ -89,135 +438,167 @@ pub struct PieChartProps {
 ///
 /// # Props
 ///
-/// - `series`: [Vec]<[f32]> (**required**): The series vector with the values.
+/// - `series`: [Vec]<[f32]> (**required**): The series vector with the values. Missing values
+///   (`f32::NAN`) are skipped like zeros. It also accepts a `Signal`, `Memo` or `ReadSignal` of it.
 /// - `labels`: [Vec]<[String]> (optional): Optional labels to show for each value of the
-/// series.
+///   series.
 /// ---
 /// - `width`: &[str] (default: `"100%"`): The SVG element width attribute. It also accepts any
-/// other CSS style, i.e., "200px"
+///   other CSS style, i.e., "200px"
 /// - `height`: &[str] (default: `"100%"`): The SVG height counter-part of the `width` prop above.
 /// - `viewbox_width`: [i32] (default: `600`): The SVG viewbox width. Together with
-/// `viewbox_height` it is useful scaling up or down the chart and labels.
+///   `viewbox_height` it is useful scaling up or down the chart and labels.
 /// - `viewbox_height`: [i32] (default: `400`): The SVG viewbox height.
 /// ---
 /// - `show_labels`: [bool] (default: `true`): Show/hide labels.
 /// - `label_position`: [`LabelPosition`] (default: [`LabelPosition::Inside`]): A hint for the
-/// automatic positioning of labels on the chart.
+///   automatic positioning of labels on the chart. Outside labels are aligned away from the pie
+///   at the end of leader lines and spread vertically on each side to avoid overlapping.
 /// - `label_offset`: [f32] (default: `0.0`): An extra offset for the labels relative to the center
-/// of the pie.
+///   of the pie.
 /// - `label_interpolation`: fn([f32]) -> [String] (optional): Function for formatting the
-/// generated labels.
+///   generated labels.
+/// - `label_mode`: [`LabelMode`] (optional): The content of the labels, from the value, its
+///   percentage of the `total` or the sum of the values, and the `labels` names. Without it, the
+///   `labels` replace the values.
 /// ---
 /// - `start_angle`: [f32] (default: `0.0`): The initial angle used for drawing the pie.
 /// - `total`: [f32] (optional): The series total sum. Can be used to make Gauge charts.
 /// - `show_ratio`: [f32] (optional): Used for making Gauge charts more easily. `0.0001` to
-/// `1.0` is the same as `0%` to `100%`.
+///   `1.0` is the same as `0%` to `100%`.
 /// - `padding`: [f32] (default: `0.0`): Padding for every side of the SVG view box.
 /// ---
+/// - `min_slice_ratio`: [f32] (optional): Group the values below this ratio of the total, from
+///   `0.0` to `1.0`, into a single "Other" slice after the rest, when there are at least two.
+/// - `max_slices`: [usize] (optional): The maximum number of slices, grouping the smallest values
+///   into the "Other" slice when there are more.
+/// - `other_label`: &[str] (default: `"Other"`): The label name of the "Other" slice. Its tooltip
+///   lists the grouped values. It is also the last slice for the `gradients` and `patterns`.
+/// ---
 /// - `donut`: [bool] (default: `false`): Draw the slices differently to make a donut-looking chart
-/// instead.
+///   instead.
 /// - `donut_width`: [f32] (default: `40.0`): The width of each donut slice.
 /// ---
+/// - `gradients`: [Vec]<[`Gradient`]> (optional): The gradient fill of each slice by value index,
+///   spread over the whole pie. Slices without a gradient keep the default colors.
+/// - `patterns`: [Vec]<[`Pattern`]> (optional): The fill pattern of each slice by value index, drawn
+///   over the slice color and taking precedence over the gradient.
+/// ---
 /// - `class_chart`: &[str] (default: `"dx-pie-chart"`): The HTML element `class` of the
-/// pie chart.
+///   pie chart.
 /// - `class_series`: &[str] (default: `"dx-series"`): The HTML element `class` for the group of
-/// pie slices.
+///   pie slices.
 /// - `class_slice`: &[str] (default: `"dx-slice"`): The HTML element `class` for all pie
-/// slices.
-/// - `class_label`: &[str] (default: `"dx-label"`): The HTML element `class` for all labels.
+///   slices.
+/// - `class_label`: &[str] (default: `"dx-label"`): The HTML element `class` for all labels, with
+///   the extra `-leader` class for the leader lines of outside labels.
+/// ---
+/// - `onerror`: [EventHandler]<[`ChartError`]> (optional): Called with the error when the props
+///   fail validation and the chart can't be drawn.
+/// - `fallback`: [Element] (optional): The element rendered instead of the chart on errors. A short
+///   error message is shown by default.
 #[allow(non_snake_case)]
 pub fn PieChart(props: PieChartProps) -> Element {
-    if props.series.is_empty() {
-        return rsx!("Pie chart error: empty series");
-    }
-
-    let center = Point::new(
-        props.viewbox_width as f32 / 2.0,
-        props.viewbox_height as f32 / 2.0,
-    );
-    let center_min = center.x.min(center.y);
-    let radius = center_min - 30.0 - props.padding;
-    let label_radius = match props.label_position {
-        LabelPosition::Inside => radius / 2.0 + props.label_offset,
-        LabelPosition::Outside => radius + props.label_offset,
-        LabelPosition::Center => 0.0 + props.label_offset,
-    };
+    // Only rebuilt when the geometry props or the series values change, see `LineChart`
+    let geometry = props.geometry();
+    let slices = use_memo(use_reactive((&geometry,), |(geometry,)| geometry.slices()));
 
-    let normalized_series = normalize_series(&props.series);
-    let normalized_sum: f32 = normalized_series.iter().sum();
+    let defs_prefix = use_defs_prefix();
 
-    let values_total: f32 = if let Some(r) = props.show_ratio {
-        1.0 / r.clamp(0.0001, 1.0) * normalized_sum
-    } else if let Some(v) = props.total {
-        (normalized_sum / props.series.iter().sum::<f32>() * v).max(normalized_sum)
-    } else {
-        normalized_sum
+    let slices = match use_validation(slices(), props.onerror) {
+        Ok(slices) => slices,
+        Err(error) => return error_element("Pie chart", &error, props.fallback),
     };
 
-    let mut m_start_angle = props.start_angle;
-    let mut color_var = 255.0;
-    let mut class_index = 0;
-    let mut label_positions = Vec::<Point>::new();
+    let raw_series = props.series.read();
+    let (series, other_indices) = geometry.grouped_series(&raw_series);
+    let other = (!other_indices.is_empty()).then(|| series.len() - 1);
 
-    let normalized_series_rsx = normalized_series.iter().filter_map(|v| {
-        if *v != 0.0 {
-            let mut end_angle = if values_total > 0.0 {
-                m_start_angle + (v / values_total) * 360.0
-            } else {
-                0.0
-            };
-            let overlap_start_angle = if class_index != 0 {
-                (m_start_angle - 0.4).max(0.0)
-            } else {
-                m_start_angle
-            };
-            if end_angle - overlap_start_angle >= 359.99 {
-                end_angle = overlap_start_angle + 359.99
+    // The tooltip of the "Other" slice listing the grouped values
+    let other_title = other_indices
+        .iter()
+        .map(|i| {
+            let value = props.format_value(raw_series[*i]);
+            match props.name(*i, None) {
+                Some(name) => format!("{name}: {value}"),
+                None => value,
             }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let mut color_var = 255.0;
 
-            let start_position = polar_to_cartesian(center, radius, overlap_start_angle);
-            let end_position = polar_to_cartesian(center, radius, end_angle);
-            let large_arc = i32::from(end_angle - m_start_angle > 180.0);
-
-            let dpath = if props.donut {
-                let donut_radius = radius - props.donut_width;
-                let start_inside_position = polar_to_cartesian(center, donut_radius, overlap_start_angle);
-                let end_inside_position = polar_to_cartesian(center, donut_radius, end_angle);
-                let large_arc_inside = large_arc;
+    let gradient = |index: usize| {
+        props
+            .gradients
+            .as_ref()
+            .and_then(|gradients| gradients.get(index))
+    };
 
-                format!("M{end_position}\
-                         A{radius},{radius},0,{large_arc},0,{start_position}\
-                         L{start_inside_position}\
-                         A{donut_radius},{donut_radius},0,{large_arc_inside},1,{end_inside_position}Z")
-            } else {
-                format!("M{end_position}\
-                         A{radius},{radius},0,{large_arc},0,{start_position}\
-                         L{center}Z")
+    let normalized_series_rsx = slices
+        .iter()
+        .enumerate()
+        .filter_map(|(index, slice)| slice.as_ref().map(|slice| (index, slice)))
+        .enumerate()
+        .map(|(class_index, (index, slice))| {
+            let color = format!("rgb({color_var}, 40, 40)");
+            let pattern = props
+                .patterns
+                .as_ref()
+                .and_then(|patterns| patterns.get(index))
+                .map(|pattern| pattern.element(format!("{defs_prefix}-pattern-{index}"), &color));
+            let fill = match (&pattern, gradient(index)) {
+                (Some(_), _) => format!("url(#{defs_prefix}-pattern-{index})"),
+                (None, Some(_)) => format!("url(#{defs_prefix}-gradient-{index})"),
+                (None, None) => color,
             };
-
             let element = rsx! {
                 g {
                     class: "{props.class_series} {props.class_series}-{class_index}",
+                    if let Some(pattern) = pattern {
+                        defs {
+                            {pattern}
+                        }
+                    }
                     path {
-                        d: "{dpath}",
+                        d: "{slice.path}",
                         class: "{props.class_slice}",
-                        fill: "rgb({color_var}, 40, 40)",
+                        fill: "{fill}",
+                        if other == Some(index) {
+                            title { "{other_title}" }
+                        }
                     },
                 }
             };
 
-            label_positions.push(polar_to_cartesian(center, label_radius, m_start_angle + (end_angle - m_start_angle) / 2.0));
-
             color_var -= 75.0 * (1.0 / (class_index + 1) as f32);
-            class_index += 1;
-            m_start_angle = end_angle;
-            Some(element)
-        } else {
-            label_positions.push(Point::new(-1.0, -1.0));
-            None
-        }
+            element
+        });
+
+    let center = geometry.center();
+    let radius = geometry.radius();
+    let bounds = Rect::new(
+        center.x - radius,
+        center.y - radius,
+        center.x + radius,
+        center.y + radius,
+    );
+    let gradients = (0..series.len()).filter_map(|index| {
+        gradient(index).map(|gradient| {
+            gradient.element(format!("{defs_prefix}-gradient-{index}"), Some(bounds))
+        })
     });
 
+    let label_texts = props.label_texts(&series, other);
+
+    // Values without a slice don't get a label
+    let slice_labels = slices
+        .iter()
+        .zip(label_texts.iter())
+        .filter(|(_, label)| !label.is_empty())
+        .filter_map(|(slice, label)| slice.as_ref().map(|slice| (slice, label)))
+        .collect::<Vec<(&Slice, &String)>>();
+
     rsx! {
         div {
             svg {
@@ -228,54 +609,34 @@ pub fn PieChart(props: PieChartProps) -> Element {
                 preserve_aspect_ratio: "xMidYMid meet",
                 xmlns: "http://www.w3.org/2000/svg",
 
+                defs {
+                    for gradient in gradients {
+                        {gradient}
+                    }
+                }
+
                 {normalized_series_rsx}
 
-                if let Some(ref labels) = props.labels {
-                    g {
-                        {
-                            label_positions.iter().zip(labels.iter()).filter_map(|(position, label)| {
-                                if position.x > 0.0 {
-                                    Some(rsx! {
-                                        text {
-                                            dx: "{position.x}",
-                                            dy: "{position.y}",
-                                            text_anchor: "middle",
-                                            class: "{props.class_label}",
-                                            alignment_baseline: "middle",
-                                            "{label}"
-                                        }
-                                    })
-                                } else {
-                                    None
-                                }
-                            })
-                        }
-                    }
-                } else if props.show_labels {
+                if !slice_labels.is_empty() {
                     g {
-                        {
-                            label_positions.iter().zip(props.series.iter()).filter_map(|(position, value)| {
-                                let label = if let Some(func) = props.label_interpolation {
-                                    func(*value)
-                                } else {
-                                    value.to_string()
-                                };
-
-                                if position.x > 0.0 {
-                                    Some(rsx! {
-                                        text {
-                                            dx: "{position.x}",
-                                            dy: "{position.y}",
-                                            text_anchor: "middle",
-                                            class: "{props.class_label}",
-                                            alignment_baseline: "middle",
-                                            "{label}"
-                                        }
-                                    })
-                                } else {
-                                    None
+                        for (slice, label) in slice_labels {
+                            if let Some(leader) = &slice.leader {
+                                polyline {
+                                    points: "{leader}",
+                                    class: "{props.class_label}-leader",
+                                    stroke: "rgb(40, 40, 40)",
+                                    stroke_width: "1",
+                                    fill: "none",
                                 }
-                            })
+                            }
+                            text {
+                                dx: "{slice.label_position.x}",
+                                dy: "{slice.label_position.y}",
+                                text_anchor: "{slice.label_anchor}",
+                                class: "{props.class_label}",
+                                alignment_baseline: "middle",
+                                "{label}"
+                            }
                         }
                     }
                 }
@@ -283,3 +644,88 @@ pub fn PieChart(props: PieChartProps) -> Element {
         }
     }
 }
+
+// The indices of the values grouped into the "Other" slice: the ones below `min_slice_ratio`
+// of the total and the smallest ones past `max_slices`, only when there are at least two
+fn other_indices(
+    series: &[f32],
+    min_slice_ratio: Option<f32>,
+    max_slices: Option<usize>,
+) -> Vec<usize> {
+    let total: f32 = series.iter().filter(|v| v.is_finite()).sum();
+    let mut indices = (0..series.len())
+        .filter(|i| series[*i].is_finite() && series[*i] > 0.0)
+        .collect::<Vec<usize>>();
+    indices.sort_by(|a, b| series[*b].total_cmp(&series[*a]));
+
+    let kept = max_slices.map_or(indices.len(), |max| {
+        if indices.len() > max {
+            max.saturating_sub(1)
+        } else {
+            max
+        }
+    });
+    let mut other = indices
+        .iter()
+        .enumerate()
+        .filter(|(rank, i)| {
+            *rank >= kept || min_slice_ratio.is_some_and(|min| series[**i] / total < min)
+        })
+        .map(|(_, i)| *i)
+        .collect::<Vec<usize>>();
+    other.sort_unstable();
+
+    if other.len() < 2 {
+        other.clear();
+    }
+    other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_groups_the_slices_past_max_slices() {
+        let series = [4.0, 1.0, 3.0, 2.0];
+
+        assert_eq!(other_indices(&series, None, None), Vec::<usize>::new());
+        assert_eq!(other_indices(&series, None, Some(4)), Vec::<usize>::new());
+        assert_eq!(other_indices(&series, None, Some(5)), Vec::<usize>::new());
+        // The "Other" slice takes the place of the last kept one
+        assert_eq!(other_indices(&series, None, Some(3)), [1, 3]);
+        assert_eq!(other_indices(&series, None, Some(1)), [0, 1, 2, 3]);
+        assert_eq!(other_indices(&series, None, Some(0)), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn other_groups_the_slices_below_min_slice_ratio() {
+        let series = [50.0, 30.0, 10.0, 6.0, 4.0];
+
+        assert_eq!(other_indices(&series, Some(0.08), None), [3, 4]);
+        assert_eq!(other_indices(&series, Some(0.2), None), [2, 3, 4]);
+        // Both rules add to the group
+        assert_eq!(other_indices(&series, Some(0.05), Some(4)), [3, 4]);
+        assert_eq!(other_indices(&series, Some(0.08), Some(3)), [2, 3, 4]);
+    }
+
+    #[test]
+    fn other_needs_at_least_two_slices() {
+        let series = [50.0, 30.0, 10.0, 6.0, 4.0];
+
+        assert_eq!(
+            other_indices(&series, Some(0.05), None),
+            Vec::<usize>::new()
+        );
+        assert_eq!(other_indices(&[3.0, 1.0], None, Some(1)), [0, 1]);
+        assert_eq!(other_indices(&[3.0], None, Some(0)), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn other_leaves_out_gaps_and_empty_values() {
+        let series = [5.0, f32::NAN, 0.0, 1.0, 1.0];
+
+        assert_eq!(other_indices(&series, Some(0.2), None), [3, 4]);
+        assert_eq!(other_indices(&series, None, Some(1)), [0, 3, 4]);
+    }
+}
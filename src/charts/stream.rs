@@ -0,0 +1,335 @@
+use std::collections::VecDeque;
+
+use dioxus::prelude::*;
+
+use crate::charts::style::SeriesStyle;
+use crate::charts::LineChart;
+use crate::error::ChartError;
+use crate::types::{Labels, Series};
+
+/// A push-style handle to the last values of a set of live series, created with the
+/// [`use_line_stream`] hook and drawn with the [`StreamingLineChart`] component.
+///
+/// It keeps a ring buffer of a fixed `window` size per series, so pushing a new point drops the
+/// oldest one once the window is full.
+#[derive(Clone, Copy, PartialEq)]
+pub struct LineStream {
+    buffers: Signal<Vec<VecDeque<f32>>>,
+    window: usize,
+    pushed: Signal<usize>,
+    extremes: Signal<Option<(f32, f32)>>,
+}
+
+impl LineStream {
+    /// Pushes one new point to every series, the first value going to the first series and so
+    /// on. Series without a value in `values` get a gap (`f32::NAN`).
+    pub fn push(&mut self, values: &[f32]) {
+        let window = self.window;
+
+        for (i, buffer) in self.buffers.write().iter_mut().enumerate() {
+            if buffer.len() == window {
+                buffer.pop_front();
+            }
+            buffer.push_back(values.get(i).copied().unwrap_or(f32::NAN));
+        }
+
+        // Values past the number of series aren't drawn, so they don't count either
+        let finite = values
+            .iter()
+            .take(self.buffers.peek().len())
+            .copied()
+            .filter(|v| v.is_finite());
+        let extremes = finite.fold(*self.extremes.peek(), |extremes, v| match extremes {
+            Some((min, max)) => Some((min.min(v), max.max(v))),
+            None => Some((v, v)),
+        });
+
+        if extremes != *self.extremes.peek() {
+            self.extremes.set(extremes);
+        }
+
+        *self.pushed.write() += 1;
+    }
+
+    /// Removes all the points from the stream.
+    pub fn clear(&mut self) {
+        for buffer in self.buffers.write().iter_mut() {
+            buffer.clear();
+        }
+        self.pushed.set(0);
+        self.extremes.set(None);
+    }
+
+    /// The number of points currently in the window.
+    pub fn len(&self) -> usize {
+        self.buffers
+            .read()
+            .first()
+            .map_or(0, |buffer: &VecDeque<f32>| buffer.len())
+    }
+
+    /// Returns `true` if no points were pushed since the stream was created or cleared.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The total number of points pushed since the stream was created or cleared.
+    pub fn pushed(&self) -> usize {
+        *self.pushed.read()
+    }
+
+    /// The size of the window, i.e. the maximum number of points kept per series.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The lowest and highest values pushed since the stream was created or cleared.
+    pub fn extremes(&self) -> Option<(f32, f32)> {
+        *self.extremes.read()
+    }
+
+    /// The series values of the whole window. The window is padded with gaps at the start while
+    /// it isn't full, so the newest point is always at the end of the x axis.
+    pub fn series(&self) -> Series {
+        self.buffers
+            .read()
+            .iter()
+            .map(|buffer| {
+                std::iter::repeat_n(f32::NAN, self.window - buffer.len())
+                    .chain(buffer.iter().copied())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Creates a [`LineStream`] with `series` series of up to `window` points each.
+pub fn use_line_stream(series: usize, window: usize) -> LineStream {
+    let window = window.max(2);
+
+    LineStream {
+        buffers: use_signal(|| vec![VecDeque::with_capacity(window); series]),
+        window,
+        pushed: use_signal(|| 0),
+        extremes: use_signal(|| None),
+    }
+}
+
+/// The `StreamingLineChart` properties struct for the configuration of the streaming line chart.
+#[allow(clippy::struct_excessive_bools)]
+#[allow(unpredictable_function_pointer_comparisons)]
+#[derive(Clone, PartialEq, Props)]
+pub struct StreamingLineChartProps {
+    stream: LineStream,
+    #[props(optional)]
+    series_labels: Option<Labels>,
+
+    #[props(default = "100%".to_string(), into)]
+    width: String,
+    #[props(default = "100%".to_string(), into)]
+    height: String,
+    #[props(default = 600)]
+    viewbox_width: i32,
+    #[props(default = 400)]
+    viewbox_height: i32,
+
+    #[props(default)]
+    padding_top: i32,
+    #[props(default)]
+    padding_bottom: i32,
+    #[props(default)]
+    padding_left: i32,
+    #[props(default)]
+    padding_right: i32,
+
+    #[props(default = true)]
+    show_grid: bool,
+    #[props(default = true)]
+    show_dotted_grid: bool,
+    #[props(default = false)]
+    show_grid_ticks: bool,
+    #[props(default = true)]
+    show_labels: bool,
+    #[props(default = false)]
+    show_dots: bool,
+
+    #[props(default = "1%".to_string(), into)]
+    line_width: String,
+    #[props(default = "3%".to_string(), into)]
+    dot_size: String,
+    #[props(optional)]
+    series_styles: Option<Vec<SeriesStyle>>,
+    #[props(optional)]
+    label_interpolation: Option<fn(f32) -> String>,
+    #[props(optional)]
+    x_label_interpolation: Option<fn(f32) -> String>,
+    #[props(default = 5)]
+    x_ticks: usize,
+
+    #[props(optional)]
+    lowest: Option<f32>,
+    #[props(optional)]
+    highest: Option<f32>,
+    #[props(default = 8)]
+    max_ticks: i32,
+    #[props(default = false)]
+    freeze_y: bool,
+
+    #[props(default = "dx-chart-line".to_string(), into)]
+    class_chart_line: String,
+
+    #[props(optional)]
+    onerror: Option<EventHandler<ChartError>>,
+    #[props(optional)]
+    fallback: Option<Element>,
+}
+
+/// This is the `StreamingLineChart` function used to render a [`LineChart`] of the points in a
+/// [`LineStream`], scrolling the x axis as new points are pushed to it.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use dioxus::prelude::*;
+/// use dioxus_charts::charts::stream::{use_line_stream, StreamingLineChart};
+///
+/// fn app() -> Element {
+///     let mut stream = use_line_stream(2, 120);
+///
+///     use_future(move || async move {
+///         loop {
+///             let (cpu, memory) = read_telemetry().await;
+///             stream.push(&[cpu, memory]);
+///         }
+///     });
+///
+///     rsx! {
+///         StreamingLineChart {
+///             stream,
+///             padding_left: 50,
+///             padding_bottom: 30,
+///             freeze_y: true,
+///             x_label_interpolation: (|v| format!("{v:.0}s")) as fn(f32) -> String,
+///         }
+///     }
+/// }
+/// ```
+///
+/// # Props
+///
+/// - `stream`: [`LineStream`] (**required**): The stream with the points to draw.
+/// - `series_labels`: [Vec]<[String]> (optional): Optional labels to show for each line.
+/// ---
+/// - `width`: &[str] (default: `"100%"`): The SVG element width attribute. It also accepts any
+///   other CSS style, i.e., "200px"
+/// - `height`: &[str] (default: `"100%"`): The SVG height counter-part of the `width` prop above.
+/// - `viewbox_width`: [i32] (default: `600`): The SVG viewbox width.
+/// - `viewbox_height`: [i32] (default: `400`): The SVG viewbox height.
+/// ---
+/// - `padding_top`: [i32] (default: `0`): Padding for the top side of the view box.
+/// - `padding_bottom`: [i32] (default: `0`): Padding for the bottom side of the view box.
+/// - `padding_left`: [i32] (default: `0`): Padding for the left side of the view box.
+/// - `padding_right`: [i32] (default: `0`): Padding for the right side of the view box.
+/// ---
+/// - `show_grid`: [bool] (default: `true`): Show/hide the chart grid.
+/// - `show_dotted_grid`: [bool] (default: `true`): Show the chart grid with dotted style or not.
+/// - `show_grid_ticks`: [bool] (default: `false`): Show the chart grid ticks instead of drawing the
+///   whole grid lines for a cleaner look.
+/// - `show_labels`: [bool] (default: `true`): Show/hide the labels.
+/// - `show_dots`: [bool] (default: `false`): Show/hide the line dots.
+/// ---
+/// - `line_width`: &[str] (default: `"1%"`): The width of the series lines.
+/// - `dot_size`: &[str] (default: `"3%"`): The size of the line dots.
+/// - `series_styles`: [Vec]<[`SeriesStyle`]> (optional): The style of each line.
+/// - `label_interpolation`: fn([f32]) -> [String] (optional): Function for formatting the
+///   generated labels of the value axis.
+/// - `x_label_interpolation`: fn([f32]) -> [String] (optional): Function for formatting the labels
+///   of the x axis, which receives the position of the label in number of points pushed.
+/// - `x_ticks`: [usize] (default: `5`): The number of labels on the x axis.
+/// ---
+/// - `lowest`: [f32] (optional): The lowest number on the chart for the value axis.
+/// - `highest`: [f32] (optional): The highest number on the chart for the value axis.
+/// - `max_ticks`: [i32] (default: `8`): The maximum number of ticks on the generated value axis.
+/// - `freeze_y`: [bool] (default: `false`): Keep every value pushed so far inside the value axis,
+///   so it only grows and doesn't jitter as points leave the window.
+/// ---
+/// - `class_chart_line`: &[str] (default: `"dx-chart-line"`): The HTML element `class` of the
+///   chart. The other elements have the same classes of the [`LineChart`] ones.
+/// ---
+/// - `onerror`: [EventHandler]<[`ChartError`]> (optional): Called with the error when the props
+///   fail validation and the chart can't be drawn.
+/// - `fallback`: [Element] (optional): The element rendered instead of the chart on errors. A short
+///   error message is shown by default.
+#[allow(non_snake_case)]
+pub fn StreamingLineChart(props: StreamingLineChartProps) -> Element {
+    let stream = props.stream;
+    let series = use_memo(move || stream.series());
+
+    let x_ticks = props.x_ticks.max(2);
+    let window = stream.window();
+    let first = stream.pushed() as f32 - window as f32;
+
+    // Labels are spread over the whole window, so they can fall between two points
+    let labels = (0..x_ticks)
+        .map(|i| {
+            let position = first + i as f32 * (window - 1) as f32 / (x_ticks - 1) as f32;
+
+            if position < 0.0 {
+                String::new()
+            } else if let Some(func) = props.x_label_interpolation {
+                func(position)
+            } else {
+                format!("{position:.0}")
+            }
+        })
+        .collect::<Labels>();
+
+    // An empty window has no values to fit the value axis to, so it gets a default range and only
+    // the grid is drawn until the first values are pushed
+    let (lowest, highest) = if series.read().iter().flatten().any(|v| v.is_finite()) {
+        (props.lowest, props.highest)
+    } else {
+        let lowest = props
+            .lowest
+            .unwrap_or_else(|| props.highest.map_or(0.0, |highest| highest - 1.0));
+        (Some(lowest), Some(props.highest.unwrap_or(lowest + 1.0)))
+    };
+
+    let domain_values = if props.freeze_y {
+        stream.extremes().map(|(min, max)| vec![min, max])
+    } else {
+        None
+    };
+
+    rsx! {
+        LineChart {
+            series,
+            labels,
+            series_labels: props.series_labels,
+            width: props.width,
+            height: props.height,
+            viewbox_width: props.viewbox_width,
+            viewbox_height: props.viewbox_height,
+            padding_top: props.padding_top,
+            padding_bottom: props.padding_bottom,
+            padding_left: props.padding_left,
+            padding_right: props.padding_right,
+            show_grid: props.show_grid,
+            show_dotted_grid: props.show_dotted_grid,
+            show_grid_ticks: props.show_grid_ticks,
+            show_labels: props.show_labels,
+            show_dots: props.show_dots,
+            line_width: props.line_width,
+            dot_size: props.dot_size,
+            series_styles: props.series_styles,
+            label_interpolation: props.label_interpolation,
+            lowest,
+            highest,
+            max_ticks: props.max_ticks,
+            domain_values,
+            class_chart_line: props.class_chart_line,
+            onerror: props.onerror,
+            fallback: props.fallback,
+        }
+    }
+}
@@ -0,0 +1,618 @@
+use dioxus::core::current_scope_id;
+use dioxus::prelude::*;
+
+use crate::types::{Point, Rect, Series};
+
+/// The shape drawn on every point of a series.
+///
+/// The markers are drawn with the series stroke color only, so they can be styled with the same
+/// CSS `stroke` rule as the lines.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Marker {
+    /// A filled circle.
+    Circle,
+    /// A filled square.
+    Square,
+    /// A filled triangle pointing up.
+    Triangle,
+    /// A filled square standing on one of its corners.
+    Diamond,
+    /// A diagonal cross.
+    Cross,
+    /// No marker.
+    None,
+    /// A custom SVG path data string using only relative commands (lowercase), which is drawn
+    /// from the center of every point in view box units, i.e. `"m-4,4 l4,-8 l4,8 z"`.
+    Path(String),
+}
+
+impl Marker {
+    // The path commands of the marker centered on `point`. The filled shapes are outlines half
+    // their size, which the stroke width returned by `stroke_width` fills up to the full `size`.
+    pub(crate) fn commands(&self, point: Point, size: f32) -> String {
+        let Point { x, y } = point;
+        let r = size / 2.0;
+
+        match self {
+            Self::Circle | Self::Square => format!("M{x},{y}h0.01"),
+            Self::Triangle => {
+                let a = size / 4.0;
+                let half_side = 3.0_f32.sqrt() * a;
+                format!(
+                    "M{x},{}L{},{}L{},{}Z",
+                    y - 2.0 * a,
+                    x + half_side,
+                    y + a,
+                    x - half_side,
+                    y + a
+                )
+            }
+            Self::Diamond => {
+                let a = size / 4.0 * 2.0_f32.sqrt();
+                format!("M{x},{}L{},{y}L{x},{}L{},{y}Z", y - a, x + a, y + a, x - a)
+            }
+            Self::Cross => format!(
+                "M{},{}L{},{}M{},{}L{},{}",
+                x - r,
+                y - r,
+                x + r,
+                y + r,
+                x + r,
+                y - r,
+                x - r,
+                y + r
+            ),
+            Self::None => String::new(),
+            Self::Path(commands) => format!("M{x},{y}{commands}"),
+        }
+    }
+
+    pub(crate) fn stroke_width(&self, size: f32) -> f32 {
+        match self {
+            Self::Circle | Self::Square => size,
+            Self::Triangle | Self::Diamond => size / 2.0,
+            Self::Cross | Self::None | Self::Path(_) => size / 4.0,
+        }
+    }
+
+    pub(crate) fn linecap(&self) -> &'static str {
+        match self {
+            Self::Circle => "round",
+            Self::Square => "square",
+            _ => "butt",
+        }
+    }
+}
+
+/// The dash pattern of a series line.
+#[derive(Clone, PartialEq, Eq)]
+pub enum LineStyle {
+    /// A continuous line.
+    Solid,
+    /// A line of dashes.
+    Dashed,
+    /// A line of dots.
+    Dotted,
+    /// A line alternating dashes and dots.
+    DashDot,
+    /// A custom SVG `stroke-dasharray`, i.e. `"6 3"`.
+    Custom(String),
+}
+
+impl LineStyle {
+    // The `stroke-dasharray` of the pattern for a line of `width`. Round caps grow every dash by
+    // the width, so they are shortened by it to keep the same look as with butt caps.
+    pub(crate) fn dash_array(&self, width: f32, round_caps: bool) -> Option<String> {
+        let pattern: &[f32] = match self {
+            Self::Solid => return None,
+            Self::Custom(dash_array) => return Some(dash_array.clone()),
+            Self::Dashed => &[4.0, 2.0],
+            Self::Dotted => &[1.0, 1.0],
+            Self::DashDot => &[4.0, 2.0, 1.0, 2.0],
+        };
+
+        let dash_array = pattern
+            .iter()
+            .enumerate()
+            .map(|(i, length)| {
+                let length = length * width;
+                match (round_caps, i % 2 == 0) {
+                    (false, _) => length,
+                    (true, true) => length - width,
+                    (true, false) => length + width,
+                }
+            })
+            .map(|length| length.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        Some(dash_array)
+    }
+}
+
+/// The style of a single series, overriding the chart-wide props and the default colors for it.
+///
+/// Every field is optional, so a style only needs the ones to change:
+///
+/// ```rust,ignore
+/// let style = SeriesStyle::default()
+///     .with_color("#8f327b")
+///     .with_line_style(LineStyle::Dashed)
+///     .with_marker(Marker::Square);
+/// ```
+///
+/// The style is set with SVG presentation attributes, which CSS rules for the chart classes still
+/// take precedence over.
+#[derive(Clone, Default, PartialEq)]
+pub struct SeriesStyle {
+    pub(crate) color: Option<String>,
+    pub(crate) stroke_width: Option<String>,
+    pub(crate) line_style: Option<LineStyle>,
+    pub(crate) dash_from: Option<usize>,
+    pub(crate) marker: Option<Marker>,
+    pub(crate) marker_size: Option<String>,
+    pub(crate) opacity: Option<f32>,
+    pub(crate) fill: Option<String>,
+    pub(crate) gradient: Option<Gradient>,
+    pub(crate) pattern: Option<Pattern>,
+    pub(crate) class: Option<String>,
+}
+
+// The style of the series without one
+static DEFAULT_STYLE: SeriesStyle = SeriesStyle {
+    color: None,
+    stroke_width: None,
+    line_style: None,
+    dash_from: None,
+    marker: None,
+    marker_size: None,
+    opacity: None,
+    fill: None,
+    gradient: None,
+    pattern: None,
+    class: None,
+};
+
+impl SeriesStyle {
+    /// The color of the series lines, markers and bars, any SVG color like `"#8f327b"`.
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// The width of the line or bars, overriding the `line_width` prop or the computed bar width.
+    pub fn with_stroke_width(mut self, width: impl Into<String>) -> Self {
+        self.stroke_width = Some(width.into());
+        self
+    }
+
+    /// The dash pattern of the line, [`LineStyle::Solid`] by default.
+    pub fn with_line_style(mut self, line_style: LineStyle) -> Self {
+        self.line_style = Some(line_style);
+        self
+    }
+
+    /// The SVG `stroke-dasharray` of the line, i.e. `"6 3"`, the same as a [`LineStyle::Custom`]
+    /// line style.
+    pub fn with_dash_array(mut self, dash_array: impl Into<String>) -> Self {
+        self.line_style = Some(LineStyle::Custom(dash_array.into()));
+        self
+    }
+
+    /// Only draws the line with its line style from the value at `index` on, keeping it solid
+    /// before, i.e. to tell the forecast values from the actual ones.
+    pub fn with_dash_from(mut self, index: usize) -> Self {
+        self.dash_from = Some(index);
+        self
+    }
+
+    /// The shape of the line dots, [`Marker::Circle`] by default.
+    pub fn with_marker(mut self, marker: Marker) -> Self {
+        self.marker = Some(marker);
+        self
+    }
+
+    /// The size of the line dots, overriding the `dot_size` prop.
+    pub fn with_marker_size(mut self, size: impl Into<String>) -> Self {
+        self.marker_size = Some(size.into());
+        self
+    }
+
+    /// The opacity of the whole series, from `0.0` to `1.0`.
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = Some(opacity);
+        self
+    }
+
+    /// The fill of the area under the line, down to the bottom of the value axis. None by default.
+    pub fn with_fill(mut self, fill: impl Into<String>) -> Self {
+        self.fill = Some(fill.into());
+        self
+    }
+
+    /// A gradient for the bars or the area under the line, instead of the flat color or fill. The
+    /// gradient of bars is spread over the whole plot area, so it follows the value axis.
+    pub fn with_gradient(mut self, gradient: Gradient) -> Self {
+        self.gradient = Some(gradient);
+        self
+    }
+
+    /// A fill pattern for the bars or the area under the line, drawn over the series color or the
+    /// fill, and taking precedence over the gradient.
+    pub fn with_pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// An extra HTML `class` for the group of elements of the series.
+    pub fn with_class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    // The style of the series at `index`, or the default one if it has none
+    pub(crate) fn get(styles: Option<&Vec<SeriesStyle>>, index: usize) -> &SeriesStyle {
+        styles
+            .and_then(|styles| styles.get(index))
+            .unwrap_or(&DEFAULT_STYLE)
+    }
+
+    // The series color, or the `rgb({color_var}, 40, 40)` default of the charts
+    pub(crate) fn color(&self, color_var: f32) -> String {
+        self.color
+            .clone()
+            .unwrap_or_else(|| format!("rgb({color_var}, 40, 40)"))
+    }
+
+    // The `class` of the series group followed by the extra style class, if any
+    pub(crate) fn class(&self, class: String) -> String {
+        match &self.class {
+            Some(extra) => format!("{class} {extra}"),
+            None => class,
+        }
+    }
+
+    // The reference to the pattern or gradient definition of the series at `index`, if any
+    pub(crate) fn paint(&self, prefix: &str, index: usize) -> Option<String> {
+        if self.pattern.is_some() {
+            Some(format!("url(#{prefix}-pattern-{index})"))
+        } else if self.gradient.is_some() {
+            Some(format!("url(#{prefix}-gradient-{index})"))
+        } else {
+            None
+        }
+    }
+
+    // The pattern definition of the series at `index` over the `color`, if it has a pattern
+    pub(crate) fn pattern_element(
+        &self,
+        prefix: &str,
+        index: usize,
+        color: &str,
+    ) -> Option<Element> {
+        self.pattern
+            .map(|pattern| pattern.element(format!("{prefix}-pattern-{index}"), color))
+    }
+}
+
+/// The error values of a series, drawn as whiskers with caps around its values, i.e. for standard
+/// deviations.
+#[derive(Clone, PartialEq)]
+pub struct ErrorBars {
+    series: usize,
+    minus: Vec<f32>,
+    plus: Vec<f32>,
+}
+
+impl ErrorBars {
+    /// Creates the error bars of the series at the `series` index, with the same error below and
+    /// above every value. Values with a missing error (`f32::NAN`) get no error bar.
+    pub fn symmetric(series: usize, errors: Vec<f32>) -> Self {
+        Self {
+            series,
+            minus: errors.clone(),
+            plus: errors,
+        }
+    }
+
+    /// Creates the error bars of the series at the `series` index, with different errors below
+    /// (`minus`) and above (`plus`) every value.
+    pub fn asymmetric(series: usize, minus: Vec<f32>, plus: Vec<f32>) -> Self {
+        Self {
+            series,
+            minus,
+            plus,
+        }
+    }
+
+    // The errors below and above the value at `index` of the series at `series`, if it has both
+    pub(crate) fn get(
+        error_bars: Option<&Vec<ErrorBars>>,
+        series: usize,
+        index: usize,
+    ) -> Option<(f32, f32)> {
+        error_bars?
+            .iter()
+            .filter(|error_bars| error_bars.series == series)
+            .find_map(|error_bars| {
+                let minus = error_bars.minus.get(index)?.abs();
+                let plus = error_bars.plus.get(index)?.abs();
+                (minus.is_finite() && plus.is_finite()).then_some((minus, plus))
+            })
+    }
+
+    // The ends of the error bars of every series value, for the value axis to include them
+    pub(crate) fn domain_values(error_bars: Option<&Vec<ErrorBars>>, series: &Series) -> Vec<f32> {
+        series
+            .iter()
+            .enumerate()
+            .flat_map(|(i, a)| {
+                a.iter().enumerate().flat_map(move |(j, v)| {
+                    Self::get(error_bars, i, j)
+                        .map_or([f32::NAN; 2], |(minus, plus)| [v - minus, v + plus])
+                })
+            })
+            .collect()
+    }
+
+    // The ends of the error bars of stacked series, which are drawn around the stack ends, for the
+    // value axis to include them
+    pub(crate) fn stacked_domain_values(
+        error_bars: Option<&Vec<ErrorBars>>,
+        series: &Series,
+    ) -> Vec<f32> {
+        let mut stack_ends = Vec::<f32>::new();
+        let mut values = Vec::<f32>::new();
+
+        for (i, a) in series.iter().enumerate() {
+            if stack_ends.len() < a.len() {
+                stack_ends.resize(a.len(), 0.0);
+            }
+
+            for (j, v) in a.iter().enumerate().filter(|(_, v)| v.is_finite()) {
+                stack_ends[j] += v;
+
+                if let Some((minus, plus)) = Self::get(error_bars, i, j) {
+                    values.extend([stack_ends[j] - minus, stack_ends[j] + plus]);
+                }
+            }
+        }
+
+        values
+    }
+
+    // The path commands of a whisker between the `low` and `high` ends, with caps of `cap` width
+    // across it
+    pub(crate) fn commands(low: Point, high: Point, cap: f32) -> String {
+        let half_cap = cap / 2.0;
+
+        if low.x == high.x {
+            format!(
+                "M{low}L{high}M{},{}h{cap}M{},{}h{cap}",
+                low.x - half_cap,
+                low.y,
+                high.x - half_cap,
+                high.y
+            )
+        } else {
+            format!(
+                "M{low}L{high}M{},{}v{cap}M{},{}v{cap}",
+                low.x,
+                low.y - half_cap,
+                high.x,
+                high.y - half_cap
+            )
+        }
+    }
+}
+
+/// A rule coloring the bars and line segments with values above a threshold, i.e. red above 90
+/// and amber above 70 for a status dashboard:
+///
+/// ```rust,ignore
+/// let thresholds = vec![
+///     Threshold::above(70.0, "#f18c27"),
+///     Threshold::above(90.0, "#d03e65"),
+/// ];
+/// ```
+///
+/// Values above several thresholds get the color of the highest one, and values below all of
+/// them keep the series color.
+#[derive(Clone, PartialEq)]
+pub struct Threshold {
+    value: f32,
+    color: String,
+}
+
+impl Threshold {
+    /// Colors the values above `value` with `color`, any SVG color like `"#d03e65"`.
+    pub fn above(value: f32, color: impl Into<String>) -> Self {
+        Self {
+            value,
+            color: color.into(),
+        }
+    }
+
+    pub(crate) fn value(&self) -> f32 {
+        self.value
+    }
+
+    // The color of the highest threshold below `value`, if any
+    pub(crate) fn color(thresholds: &[Threshold], value: f32) -> Option<&str> {
+        thresholds
+            .iter()
+            .filter(|threshold| value > threshold.value)
+            .max_by(|a, b| a.value.total_cmp(&b.value))
+            .map(|threshold| threshold.color.as_str())
+    }
+}
+
+/// A linear or radial gradient to fill a series with, made of color stops:
+///
+/// ```rust,ignore
+/// let gradient = Gradient::linear(90.0)
+///     .with_stop(0.0, "#8f327b")
+///     .with_stop(1.0, "#f18c27");
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct Gradient {
+    kind: GradientKind,
+    stops: Vec<(f32, String)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum GradientKind {
+    Linear(f32),
+    Radial,
+}
+
+impl Gradient {
+    /// A linear gradient at the `angle` in degrees, where `0.0` goes from left to right and
+    /// `90.0` from top to bottom.
+    pub fn linear(angle: f32) -> Self {
+        Self {
+            kind: GradientKind::Linear(angle),
+            stops: Vec::new(),
+        }
+    }
+
+    /// A radial gradient from the center to the border.
+    pub fn radial() -> Self {
+        Self {
+            kind: GradientKind::Radial,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Adds a color stop at the `offset`, from `0.0` to `1.0`.
+    pub fn with_stop(mut self, offset: f32, color: impl Into<String>) -> Self {
+        self.stops.push((offset.clamp(0.0, 1.0), color.into()));
+        self
+    }
+
+    // The gradient definition with the `id`, spread over the `bounds` in view coordinates when
+    // given or else over the bounding box of every element using it
+    pub(crate) fn element(&self, id: String, bounds: Option<Rect>) -> Element {
+        let units = if bounds.is_some() {
+            "userSpaceOnUse"
+        } else {
+            "objectBoundingBox"
+        };
+        let bounds = bounds.unwrap_or(Rect::new(0.0, 0.0, 1.0, 1.0));
+        let center = Point::new(
+            bounds.min.x + bounds.width() / 2.0,
+            bounds.min.y + bounds.height() / 2.0,
+        );
+
+        let stops = rsx! {
+            for (offset, color) in self.stops.iter() {
+                stop {
+                    offset: "{offset}",
+                    stop_color: "{color}",
+                }
+            }
+        };
+
+        match self.kind {
+            GradientKind::Linear(angle) => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let (dx, dy) = (cos * bounds.width() / 2.0, sin * bounds.height() / 2.0);
+
+                rsx! {
+                    linearGradient {
+                        id: "{id}",
+                        gradient_units: units,
+                        x1: "{center.x - dx}",
+                        y1: "{center.y - dy}",
+                        x2: "{center.x + dx}",
+                        y2: "{center.y + dy}",
+                        {stops}
+                    }
+                }
+            }
+            GradientKind::Radial => {
+                let radius = bounds.width().max(bounds.height()) / 2.0;
+
+                rsx! {
+                    radialGradient {
+                        id: "{id}",
+                        gradient_units: units,
+                        cx: "{center.x}",
+                        cy: "{center.y}",
+                        r: "{radius}",
+                        {stops}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A fill pattern of marks over the series color, keeping series apart without relying on colors,
+/// i.e., for colorblind readers and black-and-white print.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Pattern {
+    /// Diagonal lines going up to the right.
+    Hatch,
+    /// Diagonal lines going down to the right.
+    BackHatch,
+    /// Diagonal lines in both directions.
+    CrossHatch,
+    /// Horizontal lines.
+    Horizontal,
+    /// Vertical lines.
+    Vertical,
+    /// A grid of dots.
+    Dots,
+}
+
+// The size of the pattern tile in view units
+const PATTERN_SIZE: f32 = 8.0;
+
+impl Pattern {
+    // The marks of the pattern in a tile, with the diagonals continued over the tile corners
+    fn marks(&self) -> &'static str {
+        match self {
+            Pattern::Hatch => "M-2,2L2,-2M0,8L8,0M6,10L10,6",
+            Pattern::BackHatch => "M-2,6L2,10M0,0L8,8M6,-2L10,2",
+            Pattern::CrossHatch => "M-2,2L2,-2M0,8L8,0M6,10L10,6M-2,6L2,10M0,0L8,8M6,-2L10,2",
+            Pattern::Horizontal => "M0,4L8,4",
+            Pattern::Vertical => "M4,0L4,8",
+            Pattern::Dots => "M4,4h0",
+        }
+    }
+
+    // The pattern definition with the `id`, of white marks over the `color`
+    pub(crate) fn element(&self, id: String, color: &str) -> Element {
+        let stroke_width = match self {
+            Pattern::Dots => 3.0,
+            _ => 1.5,
+        };
+
+        rsx! {
+            pattern {
+                id: "{id}",
+                pattern_units: "userSpaceOnUse",
+                width: "{PATTERN_SIZE}",
+                height: "{PATTERN_SIZE}",
+                rect {
+                    width: "{PATTERN_SIZE}",
+                    height: "{PATTERN_SIZE}",
+                    fill: "{color}",
+                }
+                path {
+                    d: self.marks(),
+                    stroke: "white",
+                    stroke_width: "{stroke_width}",
+                    stroke_linecap: "round",
+                }
+            }
+        }
+    }
+}
+
+// The prefix of the `defs` ids of a chart instance, unique in the page. It comes from the scope of
+// the component, so the server and the client render the same ids for hydration
+pub(crate) fn use_defs_prefix() -> String {
+    use_hook(|| format!("dx-defs-{}", current_scope_id().0))
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+
+use dioxus::prelude::*;
+
+use crate::types::Series;
+
+/// The errors found while validating the configuration of a chart.
+///
+/// When a chart can't be drawn it renders its `fallback` element, or a short error message if
+/// none was given, and reports the error through its `onerror` callback.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChartError {
+    /// The chart was given no series at all.
+    NoSeries,
+    /// The series at this index has no values.
+    EmptySeries(usize),
+    /// None of the values can be drawn, e.g. they are all missing (`NaN`), or all zero for a
+    /// pie chart.
+    NoValues,
+    /// The value at this index is negative and can't be drawn as a pie slice.
+    NegativeValue(usize),
+    /// The viewbox has no area to draw on.
+    InvalidViewBox {
+        /// The viewbox width.
+        width: i32,
+        /// The viewbox height.
+        height: i32,
+    },
+    /// The padding leaves no area to draw on.
+    InvalidPadding,
+    /// The `lowest` value is not smaller than the `highest` one.
+    InvalidRange {
+        /// The lowest value of the axis.
+        lowest: f32,
+        /// The highest value of the axis.
+        highest: f32,
+    },
+}
+
+impl fmt::Display for ChartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSeries => write!(f, "no series"),
+            Self::EmptySeries(index) => write!(f, "empty series at index {index}"),
+            Self::NoValues => write!(f, "no values to draw"),
+            Self::NegativeValue(index) => write!(f, "negative value at index {index}"),
+            Self::InvalidViewBox { width, height } => {
+                write!(f, "invalid viewbox size {width}x{height}")
+            }
+            Self::InvalidPadding => write!(f, "padding larger than the viewbox"),
+            Self::InvalidRange { lowest, highest } => {
+                write!(
+                    f,
+                    "lowest value {lowest} is not below highest value {highest}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChartError {}
+
+pub(crate) fn validate_viewbox(width: i32, height: i32) -> Result<(), ChartError> {
+    if width > 0 && height > 0 {
+        Ok(())
+    } else {
+        Err(ChartError::InvalidViewBox { width, height })
+    }
+}
+
+pub(crate) fn validate_padding(
+    viewbox_width: i32,
+    viewbox_height: i32,
+    top: i32,
+    bottom: i32,
+    left: i32,
+    right: i32,
+) -> Result<(), ChartError> {
+    if left + right < viewbox_width && top + bottom < viewbox_height {
+        Ok(())
+    } else {
+        Err(ChartError::InvalidPadding)
+    }
+}
+
+pub(crate) fn validate_range(lowest: Option<f32>, highest: Option<f32>) -> Result<(), ChartError> {
+    match (lowest, highest) {
+        (Some(lowest), Some(highest)) if lowest >= highest => {
+            Err(ChartError::InvalidRange { lowest, highest })
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn validate_series(series: &Series) -> Result<(), ChartError> {
+    if series.is_empty() {
+        return Err(ChartError::NoSeries);
+    }
+
+    if let Some(index) = series.iter().position(|a| a.is_empty()) {
+        return Err(ChartError::EmptySeries(index));
+    }
+
+    if series.iter().flatten().any(|v| v.is_finite()) {
+        Ok(())
+    } else {
+        Err(ChartError::NoValues)
+    }
+}
+
+/// Passes the validation `result` through, reporting the error to `onerror` from an effect after
+/// the render and only when it changes, so a handler that updates the parent state doesn't make it
+/// render the chart again in a loop.
+pub(crate) fn use_validation<T>(
+    result: Result<T, ChartError>,
+    onerror: Option<EventHandler<ChartError>>,
+) -> Result<T, ChartError> {
+    let error = result.as_ref().err().cloned();
+
+    use_effect(use_reactive((&error,), move |(error,)| {
+        if let (Some(error), Some(handler)) = (error, onerror) {
+            handler.call(error);
+        }
+    }));
+
+    result
+}
+
+pub(crate) fn error_element(chart: &str, error: &ChartError, fallback: Option<Element>) -> Element {
+    if let Some(fallback) = fallback {
+        fallback
+    } else {
+        rsx!("{chart} error: {error}")
+    }
+}
@@ -1,18 +1,21 @@
+use std::iter;
+
 use log::debug;
 
 use crate::types::*;
-use crate::utils::magnitude;
+use crate::utils::{magnitude, text_width, TEXT_HEIGHT};
 
 const LABEL_OFFSET: f32 = 6.0;
 const TICK_SIZE: f32 = 10.0;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub enum Direction {
     Horizontal,
     Vertical,
 }
 
-#[derive(Copy, Clone)]
+#[allow(unpredictable_function_pointer_comparisons)]
+#[derive(Copy, Clone, PartialEq)]
 pub(crate) struct Axis {
     view: Rect,
     step_len: f32,
@@ -60,8 +63,35 @@ impl Axis {
         }
     }
 
+    pub fn view(&self) -> Rect {
+        self.view
+    }
+
+    // The view coordinate of the center of the category at `position`, which can be fractional.
+    // Horizontal categories start from the top, like the labels from `tick_centers`.
+    pub fn category_center(&self, position: f32) -> f32 {
+        let step = match self.direction {
+            Direction::Vertical => position + 0.5,
+            Direction::Horizontal => (self.steps - 1) as f32 - position - 0.5,
+        };
+
+        self.world_to_view(self.step_to_world(step), 0.0)
+    }
+
+    // The view size of the band of a category, the distance between two category boundaries
+    pub fn band_width(&self) -> f32 {
+        let v1 = self.world_to_view(self.step_to_world(0.0), 0.0);
+        let v2 = self.world_to_view(self.step_to_world(1.0), 0.0);
+
+        (v2 - v1).abs()
+    }
+
     pub fn step_to_world(&self, v: f32) -> f32 {
-        self.world / (self.steps as f32 - 1.0) * v
+        if self.steps > 1 {
+            self.world / (self.steps as f32 - 1.0) * v
+        } else {
+            0.0
+        }
     }
 
     pub fn lines(&self) -> Vec<Rect> {
@@ -124,6 +154,32 @@ impl Axis {
         }
     }
 
+    // The layout of the category `labels` so they don't overlap, estimated from their text width.
+    // At most `max_label_count` labels are shown, and the ones that are still too wide are rotated
+    // by 45° or 90°, and then thinned out to every nth label if even the rotated ones don't fit.
+    pub fn label_layout(&self, labels: &[String], max_label_count: Option<usize>) -> LabelLayout {
+        let step = max_label_count.map_or(1, |count| labels.len().div_ceil(count.max(1)).max(1));
+        let spacing = self.band_width() * step as f32;
+        let widest = labels
+            .iter()
+            .map(|label| text_width(label))
+            .fold(0.0, f32::max);
+
+        // A single category has no band to measure, so it's always laid out flat
+        let (rotation, thinning) = match self.direction {
+            _ if spacing <= 0.0 => (0.0, 1),
+            Direction::Vertical if widest <= spacing => (0.0, 1),
+            Direction::Vertical if TEXT_HEIGHT * std::f32::consts::SQRT_2 <= spacing => (45.0, 1),
+            Direction::Vertical => (90.0, (TEXT_HEIGHT / spacing).ceil() as usize),
+            Direction::Horizontal => (0.0, (TEXT_HEIGHT / spacing).ceil() as usize),
+        };
+
+        LabelLayout {
+            step: step * thinning.max(1),
+            rotation,
+        }
+    }
+
     pub fn centered_text_rects(&self, n_labels: i32) -> Vec<Rect> {
         let mut texts = Vec::<Rect>::new();
         let n_labels = self.steps.min(n_labels + 1);
@@ -157,6 +213,27 @@ impl Axis {
         texts
     }
 
+    // The anchors of the labels centered on the categories, in the order of `tick_centers`
+    pub fn centered_text_data(&self) -> Vec<TextData> {
+        self.tick_centers()
+            .into_iter()
+            .map(|center| match self.direction {
+                Direction::Vertical => TextData {
+                    x: center.x,
+                    y: self.view.max.y + LABEL_OFFSET,
+                    anchor: "middle",
+                    baseline: "hanging",
+                },
+                Direction::Horizontal => TextData {
+                    x: self.view.min.x - LABEL_OFFSET,
+                    y: center.y,
+                    anchor: "end",
+                    baseline: "middle",
+                },
+            })
+            .collect()
+    }
+
     pub fn text_data(&self, n_labels: usize) -> Vec<TextData> {
         let mut texts = Vec::<TextData>::new();
         let n_labels = self.steps.min(n_labels as i32);
@@ -216,6 +293,8 @@ pub(crate) struct AxisBuilder<'a> {
     stacked_series: bool,
     series: Option<&'a Series>,
     labels: Option<&'a Labels>,
+    categories: Option<usize>,
+    domain_values: &'a [f32],
 }
 
 impl<'a> Default for AxisBuilder<'a> {
@@ -233,6 +312,8 @@ impl<'a> Default for AxisBuilder<'a> {
             stacked_series: false,
             series: None,
             labels: None,
+            categories: None,
+            domain_values: &[],
         }
     }
 }
@@ -258,6 +339,12 @@ impl<'a> AxisBuilder<'a> {
         self
     }
 
+    // Extra values, besides the series ones, that the generated value axis must include
+    pub fn with_domain_values(mut self, values: &'a [f32]) -> Self {
+        self.domain_values = values;
+        self
+    }
+
     pub fn with_stacked_series(mut self, stacked: bool) -> Self {
         self.stacked_series = stacked;
         self
@@ -294,26 +381,42 @@ impl<'a> AxisBuilder<'a> {
         self
     }
 
+    pub fn with_category_count(mut self, count: usize) -> Self {
+        self.categories = Some(count);
+        self
+    }
+
     pub fn with_label_interpolation(mut self, func: Option<fn(f32) -> String>) -> Self {
         self.label_interpolation = func;
         self
     }
 
+    fn domain_values(&self) -> impl Iterator<Item = f32> + 'a {
+        self.domain_values.iter().copied().filter(|v| v.is_finite())
+    }
+
     pub fn build(self) -> Axis {
         if let Some(series) = self.series {
             let highest = if let Some(high) = self.highest {
                 high
             } else if self.stacked_series {
-                MultiZip(series.iter().map(|a| a.iter().copied()).collect())
-                    .map(|t| t.iter().sum())
-                    .reduce(f32::max)
-                    .unwrap()
+                // Shorter series are padded so the longer stacks still count
+                let categories = series.iter().map(Vec::len).max().unwrap_or(0);
+                MultiZip(
+                    series
+                        .iter()
+                        .map(|a| a.iter().copied().chain(iter::repeat(0.0)).take(categories))
+                        .collect(),
+                )
+                .map(|t| t.into_iter().filter(|v| v.is_finite()).sum())
+                .chain(self.domain_values())
+                .reduce(f32::max)
+                .unwrap_or(0.0)
             } else {
-                series
-                    .iter()
-                    .map(|a| a.iter().copied().reduce(f32::max).unwrap())
+                finite_values(series)
+                    .chain(self.domain_values())
                     .reduce(f32::max)
-                    .unwrap()
+                    .unwrap_or(0.0)
             };
 
             //if self.stacked_series {
@@ -327,11 +430,27 @@ impl<'a> AxisBuilder<'a> {
             let lowest = if let Some(low) = self.lowest {
                 low
             } else {
-                series
-                    .iter()
-                    .map(|a| a.iter().copied().reduce(f32::min).unwrap())
+                finite_values(series)
+                    .chain(self.domain_values())
                     .reduce(f32::min)
-                    .unwrap()
+                    .unwrap_or(0.0)
+            };
+
+            // A flat domain (constant, single point or all zero series) has no range to split into
+            // ticks, so it's expanded around the value by its order of magnitude, keeping any
+            // bound that was set explicitly
+            let (lowest, highest) = if highest > lowest {
+                (lowest, highest)
+            } else {
+                let pad = magnitude(highest);
+                debug!("flat domain expanded by: {}", pad);
+
+                match (self.lowest, self.highest) {
+                    (Some(_), None) => (lowest, lowest + pad),
+                    (None, Some(_)) => (highest - pad, highest),
+                    (None, None) if highest == 0.0 => (0.0, pad),
+                    _ => (highest - pad, highest + pad),
+                }
             };
 
             debug!("highest: {}", highest);
@@ -399,8 +518,7 @@ impl<'a> AxisBuilder<'a> {
                 label_size: self.label_size,
                 direction: self.direction,
             }
-        } else if let Some(labels) = self.labels {
-            let len = labels.len();
+        } else if let Some(len) = self.labels.map(Vec::len).or(self.categories) {
             let steps = if self.labels_centered {
                 len as i32 + 1
             } else {
@@ -423,6 +541,13 @@ impl<'a> AxisBuilder<'a> {
     }
 }
 
+// Missing values are represented as NaN (or any non-finite number) in a series and must not take
+// part in the domain computation, since a single NaN poisons `f32::max`/`f32::min` reductions.
+fn finite_values(series: &Series) -> impl Iterator<Item = f32> + '_ {
+    series.iter().flatten().copied().filter(|v| v.is_finite())
+}
+
+#[derive(Copy, Clone, PartialEq)]
 pub(crate) struct Grid {
     pub x: Axis,
     pub y: Axis,
@@ -436,29 +561,143 @@ impl Grid {
         }
     }
 
-    pub fn world_to_view(&self, cx: f32, cy: f32, inverted: bool) -> Point {
-        if inverted {
-            Point {
-                x: self.x.world_to_view(cx, self.x.world_start),
-                y: self.y.world_to_view(self.y.step_to_world(cy), 0.0),
-            }
-        } else {
-            Point {
-                x: self.x.world_to_view(self.x.step_to_world(cx), 0.0),
-                y: self.y.world_to_view(cy, self.y.world_start),
-            }
+    // Maps the value `cy` at the position `ratio`, from `0.0` to `1.0`, along the x axis
+    pub fn ratio_to_view(&self, ratio: f32, cy: f32) -> Point {
+        Point {
+            x: self.x.world_to_view(ratio * self.x.world, 0.0),
+            y: self.y.world_to_view(cy, self.y.world_start),
         }
     }
 
     pub fn lines(&self) -> Vec<Rect> {
         [self.x.lines().as_slice(), self.y.lines().as_slice()].concat()
     }
+}
+
+// Which category labels are shown and how much they are rotated, from `Axis::label_layout`
+#[derive(Copy, Clone, PartialEq)]
+pub(crate) struct LabelLayout {
+    step: usize,
+    rotation: f32,
+}
 
-    pub fn text_data(&self, x_n_labels: Option<usize>, y_n_labels: Option<usize>) -> Vec<TextData> {
-        [
-            self.x.text_data(x_n_labels.unwrap_or(0)).as_slice(),
-            self.y.text_data(y_n_labels.unwrap_or(0)).as_slice(),
-        ]
-        .concat()
+impl LabelLayout {
+    pub fn shows(&self, index: usize) -> bool {
+        index.is_multiple_of(self.step)
+    }
+
+    pub fn is_rotated(&self) -> bool {
+        self.rotation != 0.0
+    }
+
+    // The label `text` placed below its position, ending there when rotated
+    pub fn text(&self, text: TextData) -> TextData {
+        if self.is_rotated() {
+            TextData {
+                anchor: "end",
+                baseline: "middle",
+                ..text
+            }
+        } else {
+            text
+        }
+    }
+
+    // The `transform` attribute rotating the label `text` around its position
+    pub fn transform(&self, text: &TextData) -> Option<String> {
+        self.is_rotated()
+            .then(|| format!("rotate(-{} {} {})", self.rotation, text.x, text.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value_axis(series: &Series, lowest: Option<f32>) -> Axis {
+        Axis::builder()
+            .with_view(Rect::new(0.0, 0.0, 600.0, 400.0))
+            .with_series(series)
+            .with_lowest(lowest)
+            .with_direction(Direction::Horizontal)
+            .build()
+    }
+
+    fn assert_valid_domain(axis: &Axis, values: &[f32]) {
+        assert!(axis.steps >= 2, "steps: {}", axis.steps);
+        assert!(axis.step_len > 0.0, "step_len: {}", axis.step_len);
+        assert!(axis.world > 0.0, "world: {}", axis.world);
+
+        for v in values {
+            assert!(axis.world_start <= *v && *v <= axis.world_start + axis.world);
+
+            let view = axis.world_to_view(*v, axis.world_start);
+            assert!(view.is_finite());
+            assert!((0.0..=400.0).contains(&view), "view: {view}");
+        }
+
+        for line in axis.lines() {
+            assert!(line.min.y.is_finite() && line.max.y.is_finite());
+        }
+    }
+
+    #[test]
+    fn constant_series() {
+        let series = vec![vec![42.0; 10]];
+        let axis = value_axis(&series, None);
+
+        assert_valid_domain(&axis, &[42.0]);
+        assert!(axis.world_start < 42.0);
+    }
+
+    #[test]
+    fn single_point_series() {
+        let series = vec![vec![-3.5]];
+        let axis = value_axis(&series, None);
+
+        assert_valid_domain(&axis, &[-3.5]);
+    }
+
+    #[test]
+    fn all_zero_series() {
+        let series = vec![vec![0.0; 5], vec![0.0; 5]];
+        let axis = value_axis(&series, None);
+
+        assert_valid_domain(&axis, &[0.0]);
+        assert_eq!(axis.world_start, 0.0);
+    }
+
+    #[test]
+    fn all_zero_series_with_fixed_lowest() {
+        let series = vec![vec![0.0; 5]];
+        let axis = value_axis(&series, Some(0.0));
+
+        assert_valid_domain(&axis, &[0.0]);
+        assert_eq!(axis.world_start, 0.0);
+    }
+
+    #[test]
+    fn constant_series_with_gaps() {
+        let series = vec![vec![f32::NAN, 7.0, f32::NAN, 7.0]];
+        let axis = value_axis(&series, None);
+
+        assert_valid_domain(&axis, &[7.0]);
+    }
+
+    #[test]
+    fn single_label_layout() {
+        let labels = vec!["A rather long category label".to_string()];
+
+        for direction in [Direction::Vertical, Direction::Horizontal] {
+            let axis = Axis::builder()
+                .with_view(Rect::new(0.0, 0.0, 600.0, 400.0))
+                .with_labels(Some(&labels))
+                .with_direction(direction)
+                .build();
+            let layout = axis.label_layout(&labels, None);
+
+            assert!(!layout.is_rotated());
+            assert_eq!(layout.step, 1);
+        }
     }
 }
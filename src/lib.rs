@@ -9,8 +9,10 @@ The components available are:
 
 - [PieChart](crate::charts::PieChart): for Pie, Donut and Gauge charts
 - [BarChart](crate::charts::BarChart): for Bar and Stacked Bar charts, vertical
-or horizontal
+  or horizontal
 - [LineChart](crate::charts::LineChart)
+- [StreamingLineChart](crate::charts::StreamingLineChart): a line chart for live data pushed
+  to a fixed-size window
 
 # Usage
 This crate is [on crates.io](https://crates.io/crates/dioxus-charts) and can be
@@ -26,6 +28,7 @@ dioxus-charts = "0.1.3"
 
 #![deny(missing_docs)]
 
+mod error;
 mod grid;
 mod types;
 mod utils;
@@ -37,17 +40,37 @@ pub mod charts {
     //! - [PieChart](crate::charts::PieChart)
     //! - [BarChart](crate::charts::BarChart)
     //! - [LineChart](crate::charts::LineChart)
+    //! - [StreamingLineChart](crate::charts::StreamingLineChart)
 
+    /// Module for the [Annotation](annotation::Annotation) and [Callout](annotation::Callout)
+    /// types of the line and bar charts
+    pub mod annotation;
     /// Module for the [BarChart](pie::PieChart) component and its configuration types
     pub mod bar;
     /// Module for the [LineChart](pie::PieChart) component and its configuration types
     pub mod line;
     /// Module for the [PieChart](pie::PieChart) component and its configuration types
     pub mod pie;
+    /// Module for the [StreamingLineChart](stream::StreamingLineChart) component and its
+    /// [LineStream](stream::LineStream) handle
+    pub mod stream;
+    /// Module for the style types shared by the chart components, like the
+    /// [Marker](style::Marker) shapes, [LineStyle](style::LineStyle) dash patterns and the
+    /// [SeriesStyle](style::SeriesStyle) and [ErrorBars](style::ErrorBars) of each series and the
+    /// [Threshold](style::Threshold) color rules, [Gradient](style::Gradient) and
+    /// [Pattern](style::Pattern) fills
+    pub mod style;
 
+    pub use annotation::{Annotation, Callout};
     pub use bar::BarChart;
     pub use line::LineChart;
     pub use pie::PieChart;
+    pub use stream::StreamingLineChart;
+    pub use style::{ErrorBars, Gradient, LineStyle, Marker, Pattern, SeriesStyle, Threshold};
 }
 
-pub use crate::charts::{BarChart, LineChart, PieChart};
+pub use crate::charts::{
+    Annotation, BarChart, Callout, ErrorBars, Gradient, LineChart, LineStyle, Marker, Pattern,
+    PieChart, SeriesStyle, StreamingLineChart, Threshold,
+};
+pub use crate::error::ChartError;
@@ -9,10 +9,315 @@ pub(crate) fn polar_to_cartesian(c: Point, radius: f32, angle_degrees: f32) -> P
 }
 
 pub(crate) fn normalize_series(series: &[f32]) -> Vec<f32> {
-    let r = series.iter().copied().reduce(f32::max).unwrap() / 100.0;
-    series.iter().map(|v| v / r).collect()
+    let max = series
+        .iter()
+        .copied()
+        .filter(|v| v.is_finite())
+        .reduce(f32::max)
+        .unwrap_or(0.0);
+
+    if max <= 0.0 {
+        return vec![0.0; series.len()];
+    }
+
+    let r = max / 100.0;
+    series
+        .iter()
+        .map(|v| if v.is_finite() { v / r } else { 0.0 })
+        .collect()
 }
 
 pub(crate) fn magnitude(value: f32) -> f32 {
+    if value == 0.0 || !value.is_finite() {
+        return 1.0;
+    }
+
     10.0_f32.powf(value.abs().log10().floor())
 }
+
+// Largest-Triangle-Three-Buckets downsampling of `points` to `threshold` points, keeping the first
+// and last ones and, from every bucket in between, the one forming the largest triangle with the
+// previously kept point and the average of the next bucket.
+pub(crate) fn lttb(points: &[(usize, f32)], threshold: usize) -> Vec<(usize, f32)> {
+    if threshold >= points.len() || threshold < 3 {
+        return points.to_vec();
+    }
+
+    let len = points.len();
+    let every = (len - 2) as f32 / (threshold - 2) as f32;
+    let mut sampled = Vec::with_capacity(threshold);
+    let mut a = points[0];
+    sampled.push(a);
+
+    for i in 0..threshold - 2 {
+        // The average of the next bucket, which for the last one is just the last point
+        let next_start = (((i + 1) as f32 * every) as usize + 1).min(len - 1);
+        let next_end = (((i + 2) as f32 * every) as usize + 1).clamp(next_start + 1, len);
+        let next = &points[next_start..next_end];
+        let avg_x = next.iter().map(|p| p.0 as f32).sum::<f32>() / next.len() as f32;
+        let avg_y = next.iter().map(|p| p.1).sum::<f32>() / next.len() as f32;
+
+        let start = (i as f32 * every) as usize + 1;
+        let end = next_start.max(start + 1);
+
+        let mut max_area = -1.0;
+        let mut chosen = points[start];
+
+        for p in &points[start..end] {
+            let area = ((a.0 as f32 - avg_x) * (p.1 - a.1)
+                - (a.0 as f32 - p.0 as f32) * (avg_y - a.1))
+                .abs();
+            if area > max_area {
+                max_area = area;
+                chosen = *p;
+            }
+        }
+
+        sampled.push(chosen);
+        a = chosen;
+    }
+
+    sampled.push(points[len - 1]);
+    sampled
+}
+
+// Min/max bucket downsampling of `points` to about `threshold` points, keeping the first and last
+// ones and the lowest and highest ones of every bucket in between, in their original order so
+// spikes are never lost.
+pub(crate) fn min_max_buckets(points: &[(usize, f32)], threshold: usize) -> Vec<(usize, f32)> {
+    if threshold >= points.len() || threshold < 4 {
+        return points.to_vec();
+    }
+
+    let len = points.len();
+    let inner = &points[1..len - 1];
+    let buckets = (threshold - 2) / 2;
+    let every = inner.len() as f32 / buckets as f32;
+    let mut sampled = Vec::with_capacity(buckets * 2 + 2);
+    sampled.push(points[0]);
+
+    for i in 0..buckets {
+        let start = (i as f32 * every) as usize;
+        let end = (((i + 1) as f32 * every) as usize).min(inner.len());
+        let bucket = &inner[start..end.max(start + 1)];
+
+        let min = bucket
+            .iter()
+            .copied()
+            .reduce(|a, b| if b.1 < a.1 { b } else { a });
+        let max = bucket
+            .iter()
+            .copied()
+            .reduce(|a, b| if b.1 > a.1 { b } else { a });
+
+        if let (Some(min), Some(max)) = (min, max) {
+            match min.0.cmp(&max.0) {
+                std::cmp::Ordering::Less => sampled.extend([min, max]),
+                std::cmp::Ordering::Greater => sampled.extend([max, min]),
+                std::cmp::Ordering::Equal => sampled.push(min),
+            }
+        }
+    }
+
+    sampled.push(points[len - 1]);
+    sampled
+}
+
+// Parses an SVG length like `"3%"`, `"4px"` or `"4"` into view box units. Percentages are relative
+// to the normalized diagonal of the view box, the same as for the `stroke-width` attribute.
+pub(crate) fn parse_length(length: &str, viewbox_width: f32, viewbox_height: f32) -> Option<f32> {
+    let length = length.trim();
+
+    if let Some(percent) = length.strip_suffix('%') {
+        let diagonal = ((viewbox_width.powi(2) + viewbox_height.powi(2)) / 2.0).sqrt();
+        percent
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .map(|p| p / 100.0 * diagonal)
+    } else {
+        length
+            .strip_suffix("px")
+            .unwrap_or(length)
+            .trim()
+            .parse()
+            .ok()
+    }
+}
+
+// A length prop in view box units, or the `default` view length when it can't be parsed
+pub(crate) fn view_length(
+    length: &str,
+    default: f32,
+    viewbox_width: i32,
+    viewbox_height: i32,
+) -> f32 {
+    parse_length(length, viewbox_width as f32, viewbox_height as f32).unwrap_or(default)
+}
+
+// The estimated size of the characters of labels in view units, without measuring the font
+const CHAR_WIDTH: f32 = 7.0;
+pub(crate) const TEXT_HEIGHT: f32 = 12.0;
+
+// The estimated view width of the label `text`
+pub(crate) fn text_width(text: &str) -> f32 {
+    text.chars().count() as f32 * CHAR_WIDTH
+}
+
+// Spreads the `positions` of labels along one axis so they are at least `gap` apart and within
+// `min` and `max`, moving them as little as possible. Overlapping labels are merged into clusters
+// centered on the mean of their positions, which are then merged again while they overlap. When
+// there isn't room for all of them, they are spread evenly from `min` to `max` instead.
+pub(crate) fn relax_positions(positions: &[f32], gap: f32, min: f32, max: f32) -> Vec<f32> {
+    let mut order = (0..positions.len()).collect::<Vec<usize>>();
+    order.sort_by(|a, b| positions[*a].total_cmp(&positions[*b]));
+
+    // The distance between the labels of a cluster, less than `gap` when they don't fit
+    let step = |count: usize| {
+        if count > 1 {
+            gap.min((max - min).max(0.0) / (count - 1) as f32)
+        } else {
+            gap
+        }
+    };
+
+    // The start, sum of the wanted positions and labels of every cluster, from the lowest one
+    let mut clusters = Vec::<(f32, f32, Vec<usize>)>::new();
+    let place = |sum: f32, count: usize| {
+        let length = (count - 1) as f32 * step(count);
+        let start = sum / count as f32 - length / 2.0;
+        start.clamp(min, (max - length).max(min))
+    };
+
+    for index in order {
+        clusters.push((place(positions[index], 1), positions[index], vec![index]));
+
+        while let [.., (start, _, labels), (next, _, _)] = clusters.as_slice() {
+            if start + labels.len() as f32 * gap <= *next {
+                break;
+            }
+
+            let (_, sum, labels) = clusters.pop().unwrap_or_default();
+            if let Some(last) = clusters.last_mut() {
+                last.1 += sum;
+                last.2.extend(labels);
+                last.0 = place(last.1, last.2.len());
+            }
+        }
+    }
+
+    let mut relaxed = positions.to_vec();
+    for (start, _, labels) in clusters {
+        let step = step(labels.len());
+        for (i, index) in labels.into_iter().enumerate() {
+            relaxed[index] = start + i as f32 * step;
+        }
+    }
+
+    relaxed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave(len: usize) -> Vec<(usize, f32)> {
+        (0..len).map(|i| (i, (i as f32 / 20.0).sin())).collect()
+    }
+
+    fn assert_sampled(sampled: &[(usize, f32)], points: &[(usize, f32)], threshold: usize) {
+        assert!(sampled.len() <= threshold, "len: {}", sampled.len());
+        assert_eq!(sampled.first(), points.first());
+        assert_eq!(sampled.last(), points.last());
+        assert!(sampled.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn lttb_keeps_the_ends_in_order() {
+        let points = wave(1000);
+
+        for threshold in [3, 10, 99, 500] {
+            let sampled = lttb(&points, threshold);
+            assert_eq!(sampled.len(), threshold);
+            assert_sampled(&sampled, &points, threshold);
+        }
+    }
+
+    #[test]
+    fn lttb_passes_small_inputs_and_thresholds_through() {
+        let points = wave(100);
+
+        assert_eq!(lttb(&points, 2), points);
+        assert_eq!(lttb(&points, 100), points);
+        assert_eq!(lttb(&points, 200), points);
+    }
+
+    #[test]
+    fn min_max_buckets_keeps_the_ends_in_order() {
+        let points = wave(1000);
+
+        for threshold in [4, 11, 100, 500] {
+            assert_sampled(&min_max_buckets(&points, threshold), &points, threshold);
+        }
+    }
+
+    #[test]
+    fn min_max_buckets_keeps_the_spikes() {
+        let mut points = wave(1000);
+        points[321].1 = 10.0;
+        points[654].1 = -10.0;
+
+        let sampled = min_max_buckets(&points, 20);
+        assert!(sampled.contains(&points[321]));
+        assert!(sampled.contains(&points[654]));
+    }
+
+    #[test]
+    fn min_max_buckets_passes_small_inputs_and_thresholds_through() {
+        let points = wave(100);
+
+        assert_eq!(min_max_buckets(&points, 3), points);
+        assert_eq!(min_max_buckets(&points, 100), points);
+        assert_eq!(min_max_buckets(&points, 200), points);
+    }
+
+    #[test]
+    fn relax_positions_keeps_separate_positions() {
+        let positions = [40.0, 10.0, 80.0];
+
+        assert_eq!(relax_positions(&positions, 12.0, 0.0, 100.0), positions);
+    }
+
+    #[test]
+    fn relax_positions_spreads_ties_around_them() {
+        assert_eq!(
+            relax_positions(&[50.0, 50.0], 10.0, 0.0, 100.0),
+            [45.0, 55.0]
+        );
+        assert_eq!(
+            relax_positions(&[50.0, 50.0, 50.0], 10.0, 0.0, 100.0),
+            [40.0, 50.0, 60.0]
+        );
+    }
+
+    #[test]
+    fn relax_positions_clamps_to_the_bounds() {
+        assert_eq!(relax_positions(&[2.0, 0.0], 10.0, 5.0, 100.0), [15.0, 5.0]);
+        assert_eq!(
+            relax_positions(&[99.0, 100.0], 10.0, 0.0, 100.0),
+            [90.0, 100.0]
+        );
+    }
+
+    #[test]
+    fn relax_positions_fits_too_many_positions_in_the_bounds() {
+        assert_eq!(
+            relax_positions(&[18.0, 18.0, 18.0, 18.0], 12.0, 6.0, 30.0),
+            [6.0, 14.0, 22.0, 30.0]
+        );
+        assert_eq!(
+            relax_positions(&[18.0, 18.0], 12.0, 10.0, 10.0),
+            [10.0, 10.0]
+        );
+    }
+}